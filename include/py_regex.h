@@ -0,0 +1,61 @@
+/*
+ * C interface of py-regex, built with the `ffi` feature:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Objects returned through an output pointer are owned by the caller and must be released
+ * exactly once with the matching free function. Strings returned by the library are owned by
+ * it: group texts stay valid until their match is freed, and the error message until the next
+ * call on the same thread. Input strings are NUL-terminated UTF-8 and only borrowed for the call.
+ */
+
+#ifndef PY_REGEX_H
+#define PY_REGEX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define PY_REGEX_OK 0
+#define PY_REGEX_NO_MATCH 1
+#define PY_REGEX_NO_GROUP 2
+#define PY_REGEX_ERROR_ARGUMENT (-1)
+#define PY_REGEX_ERROR_UTF8 (-2)
+#define PY_REGEX_ERROR_PYTHON (-3)
+#define PY_REGEX_ERROR_PANIC (-4)
+
+typedef struct PyRegexHandle PyRegexHandle;
+typedef struct PyRegexMatchHandle PyRegexMatchHandle;
+
+/* Compiles `pattern` with the given flag bits (as in Python's `regex` module). */
+int py_regex_compile(const char *pattern, uint32_t flags, PyRegexHandle **out);
+
+/* Searches `text`; returns PY_REGEX_NO_MATCH and stores NULL if there is no match.
+ * Returns PY_REGEX_ERROR_PYTHON if Python reports a group span outside `text`. */
+int py_regex_search(const PyRegexHandle *re, const char *text, PyRegexMatchHandle **out);
+
+/* Returns the number of groups, including group 0. */
+size_t py_regex_match_len(const PyRegexMatchHandle *m);
+
+/* Stores the text and byte span of a group; any output pointer may be NULL.
+ * Returns PY_REGEX_NO_GROUP if the group did not participate. */
+int py_regex_match_group(const PyRegexMatchHandle *m, size_t group, const char **text,
+                         size_t *start, size_t *end);
+
+/* Returns the number of a named group, or -1 if there is none or an argument is invalid. */
+ptrdiff_t py_regex_match_group_index(const PyRegexMatchHandle *m, const char *name);
+
+/* Returns the message of the last error on this thread, or NULL. */
+const char *py_regex_last_error(void);
+
+void py_regex_free(PyRegexHandle *re);
+void py_regex_match_free(PyRegexMatchHandle *m);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PY_REGEX_H */
@@ -0,0 +1,139 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use std::process::Command;
+use syn::{Data, DeriveInput, Fields, LitStr, Type, parse_macro_input};
+
+/// Compiles the pattern with the Python `regex` module at build time, so invalid patterns are
+/// reported as compile errors, and expands to `py_regex::lazy_pyregex!(pattern)`.
+///
+/// The interpreter is taken from `PYO3_PYTHON` (as for pyo3 itself) and defaults to `python3`;
+/// it must have the `regex` package installed.
+#[proc_macro]
+pub fn py_regex(input: TokenStream) -> TokenStream {
+    let pattern = parse_macro_input!(input as LitStr);
+    if let Err(message) = validate(&pattern.value()) {
+        return syn::Error::new(pattern.span(), message)
+            .to_compile_error()
+            .into();
+    }
+    quote!(::py_regex::lazy_pyregex!(#pattern)).into()
+}
+
+/// Exits with 1 and the error message for invalid patterns, or with 2 if `regex` is missing.
+const VALIDATE: &str = "\
+import sys
+try:
+    import regex
+except ImportError as e:
+    sys.stderr.write(str(e))
+    sys.exit(2)
+try:
+    regex.compile(sys.argv[1])
+except regex.error as e:
+    sys.stderr.write(str(e))
+    sys.exit(1)
+";
+
+fn validate(pattern: &str) -> Result<(), String> {
+    let python = std::env::var("PYO3_PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let output = Command::new(&python)
+        .args(["-c", VALIDATE, pattern])
+        .output()
+        .map_err(|err| format!("cannot run `{python}` to validate the pattern: {err}"))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match output.status.code() {
+        Some(0) => Ok(()),
+        Some(1) => Err(format!("invalid pattern: {}", stderr.trim())),
+        _ => Err(format!(
+            "cannot validate the pattern with `{python}`: {}",
+            stderr.trim()
+        )),
+    }
+}
+
+/// Implements `py_regex::FromCaptures` for a struct with named fields, filling each field from
+/// the group of the same name (or `#[capture(name = "...")]`) with `FromStr`.
+/// `Option<T>` fields are `None` when their group did not participate; other fields are required.
+#[proc_macro_derive(FromCaptures, attributes(capture))]
+pub fn derive_from_captures(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match from_captures_impl(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn from_captures_impl(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "FromCaptures can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "FromCaptures requires a struct with named fields",
+        ));
+    };
+    let mut inits = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().unwrap();
+        let mut group = LitStr::new(&ident.to_string(), ident.span());
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("capture"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    group = meta.value()?.parse()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `name = \"...\"`"))
+                }
+            })?;
+        }
+        inits.push(match option_inner(&field.ty) {
+            Some(inner) => quote! {
+                #ident: ::py_regex::__private::parse_group::<#inner>(data, #group)?
+            },
+            None => {
+                let ty = &field.ty;
+                quote! {
+                    #ident: ::py_regex::__private::parse_group::<#ty>(data, #group)?
+                        .ok_or_else(|| ::py_regex::__private::missing_group(#group))?
+                }
+            }
+        });
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::py_regex::FromCaptures for #name #ty_generics #where_clause {
+            fn from_captures(
+                data: &::py_regex::MatchData,
+            ) -> ::py_regex::PyResult<Self> {
+                ::std::result::Result::Ok(Self { #(#inits,)* })
+            }
+        }
+    })
+}
+
+/// Returns `T` if the type is written as `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,132 @@
+use crate::haystack::PyText;
+use crate::helpers::helpers;
+use crate::match_data::MatchData;
+use crate::python::{PyRegex, match_data_from};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// How a pattern matched a text, as returned by `PyRegex::stats_for`. Lengths are in code points.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    /// The number of matches.
+    pub count: usize,
+    /// The combined length of the matches.
+    pub total_len: usize,
+    /// The length of the shortest match, if any.
+    pub min_len: Option<usize>,
+    /// The length of the longest match, if any.
+    pub max_len: Option<usize>,
+    /// For each named group, the number of matches it participated in.
+    pub group_participation: HashMap<String, usize>,
+}
+
+impl MatchStats {
+    /// Returns the mean length of the matches, if any.
+    pub fn mean_len(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.total_len as f64 / self.count as f64)
+    }
+}
+
+impl PyRegex {
+    /// Buckets the matches by the text of the named group, in one pass over the text. Each
+    /// bucket keeps its matches in order; matches in which the group did not participate are
+    /// left out. Returns an `IndexError` if the pattern has no such group.
+    ///
+    /// ```
+    /// let re = py_regex::PyRegex::new(r"(?P<level>ERROR|WARN) (?P<msg>\w+)")?;
+    /// let by_level = re.group_matches_by("ERROR disk WARN cpu ERROR net", "level")?;
+    /// assert_eq!(by_level["ERROR"].len(), 2);
+    /// assert_eq!(by_level["WARN"][0].name("msg"), Some("cpu"));
+    /// # Ok::<(), pyo3::PyErr>(())
+    /// ```
+    pub fn group_matches_by<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+        group_name: &str,
+    ) -> PyResult<HashMap<String, Vec<MatchData>>> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            let mut groups = HashMap::<String, Vec<MatchData>>::new();
+            let matches = helpers(py)?.call_method1(
+                "group_matches",
+                (
+                    self.as_py(),
+                    text.to_py_str(py),
+                    group_name,
+                    self.kwargs(py),
+                ),
+            )?;
+            for item in matches.try_iter()? {
+                let (key, parts) = item?.extract::<(String, Bound<PyAny>)>()?;
+                groups
+                    .entry(key)
+                    .or_default()
+                    .push(match_data_from(&parts)?);
+            }
+            Ok(groups)
+        });
+        operation.finish_counting(result, |groups| groups.values().map(Vec::len).sum())
+    }
+
+    /// Summarizes the matches in the text in one pass on the Python side, without creating any
+    /// match on the Rust side, e.g. to profile how a rule behaves on a sample of a corpus.
+    pub fn stats_for<T: PyText + ?Sized>(&self, text: &T) -> PyResult<MatchStats> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            let (count, total_len, min_len, max_len, group_participation) = helpers(py)?
+                .call_method1(
+                    "match_stats",
+                    (self.as_py(), text.to_py_str(py), self.kwargs(py)),
+                )?
+                .extract()?;
+            Ok(MatchStats {
+                count,
+                total_len,
+                min_len,
+                max_len,
+                group_participation,
+            })
+        });
+        operation.finish_counting(result, |stats| stats.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(?P<user>\w+)(?:@(?P<host>\w+))?")?;
+        let groups = re.group_matches_by("a@x b@y é c@x", "host")?;
+        assert_eq!(groups.len(), 2);
+        let users = |host: &str| {
+            groups[host]
+                .iter()
+                .map(|m| m["user"].to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(users("x"), ["a", "c"]);
+        assert_eq!(users("y"), ["b"]);
+        assert_eq!(groups["x"][1].span(0), Some(10..13));
+
+        assert!(re.group_matches_by("a", "nope").is_err());
+
+        let stats = re.stats_for("a@x bb é c@xyz")?;
+        assert_eq!((stats.count, stats.total_len), (4, 11));
+        assert_eq!((stats.min_len, stats.max_len), (Some(1), Some(5)));
+        assert_eq!(stats.mean_len(), Some(2.75));
+        assert_eq!(stats.group_participation["user"], 4);
+        assert_eq!(stats.group_participation["host"], 2);
+        let empty = re.stats_for("")?;
+        assert_eq!(
+            (empty.count, empty.max_len, empty.mean_len()),
+            (0, None, None)
+        );
+        assert_eq!(empty.group_participation["host"], 0);
+
+        Ok(())
+    }
+}
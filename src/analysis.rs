@@ -0,0 +1,285 @@
+use std::fmt;
+
+/// A construct that the `regex` crate does not support, found by `analyze`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Construct {
+    /// `\1`, `\g<name>` or `(?P=name)`.
+    Backreference,
+    /// `(?=...)` or `(?!...)`.
+    Lookahead,
+    /// `(?<=...)` or `(?<!...)`.
+    Lookbehind,
+    /// `(?R)`, `(?1)`, `(?&name)` or `(?P>name)`.
+    Recursion,
+    /// Fuzzy constraints such as `{e<=1}`.
+    Fuzzy,
+    /// `(?>...)`.
+    AtomicGroup,
+    /// `*+`, `++`, `?+` or `{m,n}+`.
+    PossessiveQuantifier,
+    /// `(?(1)yes|no)`.
+    Conditional,
+    /// `(?|...|...)`.
+    BranchReset,
+    /// `\L<name>`.
+    NamedList,
+    /// `\G`, the position where the search started.
+    SearchAnchor,
+    /// `\K`, which drops the text matched so far from the match.
+    KeepOut,
+}
+
+impl fmt::Display for Construct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Construct::Backreference => "backreference",
+            Construct::Lookahead => "lookahead",
+            Construct::Lookbehind => "lookbehind",
+            Construct::Recursion => "recursion",
+            Construct::Fuzzy => "fuzzy matching",
+            Construct::AtomicGroup => "atomic group",
+            Construct::PossessiveQuantifier => "possessive quantifier",
+            Construct::Conditional => "conditional",
+            Construct::BranchReset => "branch reset",
+            Construct::NamedList => "named list",
+            Construct::SearchAnchor => r"\G anchor",
+            Construct::KeepOut => r"\K",
+        })
+    }
+}
+
+/// A construct and the byte offset in the pattern where it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    /// The construct.
+    pub construct: Construct,
+    /// The byte offset of the construct in the pattern.
+    pub offset: usize,
+}
+
+/// The result of `analyze`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatternReport {
+    /// The constructs that need the Python engine, in pattern order.
+    pub findings: Vec<Finding>,
+}
+
+impl PatternReport {
+    /// Returns `true` if nothing in the pattern needs the Python engine, so it can be routed to
+    /// the `regex` crate. Some escapes still differ in meaning, e.g. `\Z` is `\z` there.
+    pub fn is_portable(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns `true` if the pattern uses the construct.
+    pub fn uses(&self, construct: Construct) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.construct == construct)
+    }
+}
+
+/// Scans the pattern for constructs that require the Python engine, without compiling it.
+///
+/// The scan is syntactic: it does not validate the pattern, and in verbose mode comments are
+/// scanned like the rest of the pattern.
+///
+/// ```
+/// use py_regex::{Construct, analyze};
+///
+/// assert!(analyze(r"\d{4}-\d{2}").is_portable());
+/// assert!(analyze(r"(?<=\$)\d+").uses(Construct::Lookbehind));
+/// ```
+pub fn analyze(pattern: &str) -> PatternReport {
+    let bytes = pattern.as_bytes();
+    let mut findings = Vec::new();
+    let mut found = |construct, offset| findings.push(Finding { construct, offset });
+    let mut i = 0;
+    // Whether the previous token was a quantifier, which makes a following `+` possessive.
+    let mut after_quantifier = false;
+    while i < bytes.len() {
+        let start = i;
+        let quantifier = after_quantifier;
+        after_quantifier = false;
+        match bytes[i] {
+            b'\\' => {
+                i += 1;
+                match bytes.get(i) {
+                    Some(b'1'..=b'9') => {
+                        let octal = bytes.len() >= i + 3
+                            && bytes[i..i + 3].iter().all(|b| (b'0'..=b'7').contains(b));
+                        if !octal {
+                            found(Construct::Backreference, start);
+                        }
+                    }
+                    Some(b'g') if bytes.get(i + 1) == Some(&b'<') => {
+                        found(Construct::Backreference, start)
+                    }
+                    Some(b'L') if bytes.get(i + 1) == Some(&b'<') => {
+                        found(Construct::NamedList, start)
+                    }
+                    Some(b'G') => found(Construct::SearchAnchor, start),
+                    Some(b'K') => found(Construct::KeepOut, start),
+                    Some(b'p' | b'P' | b'N' | b'x' | b'u') if bytes.get(i + 1) == Some(&b'{') => {
+                        i = skip_past(bytes, i, b'}') - 1;
+                    }
+                    _ => {}
+                }
+                i += bytes.get(i).map_or(1, |&b| utf8_len(b));
+            }
+            b'[' => i = skip_class(bytes, i),
+            b'(' if bytes.get(i + 1) == Some(&b'?') => {
+                let rest = &bytes[i + 2..];
+                let construct = if rest.starts_with(b"=") || rest.starts_with(b"!") {
+                    Some(Construct::Lookahead)
+                } else if rest.starts_with(b"<=") || rest.starts_with(b"<!") {
+                    Some(Construct::Lookbehind)
+                } else if rest.starts_with(b"P=") {
+                    Some(Construct::Backreference)
+                } else if rest.starts_with(b">") {
+                    Some(Construct::AtomicGroup)
+                } else if rest.starts_with(b"(") {
+                    Some(Construct::Conditional)
+                } else if rest.starts_with(b"|") {
+                    Some(Construct::BranchReset)
+                } else if rest.starts_with(b"R)")
+                    || rest.starts_with(b"&")
+                    || rest.starts_with(b"P>")
+                    || rest
+                        .iter()
+                        .position(|&b| b == b')')
+                        .is_some_and(|end| is_group_number(&rest[..end]))
+                {
+                    Some(Construct::Recursion)
+                } else {
+                    None
+                };
+                if let Some(construct) = construct {
+                    found(construct, start);
+                }
+                if rest.starts_with(b"#") {
+                    i = skip_past(bytes, i, b')');
+                } else {
+                    i += 2;
+                }
+            }
+            b'*' | b'+' | b'?' => {
+                if bytes[i] == b'+' && quantifier {
+                    found(Construct::PossessiveQuantifier, start);
+                } else {
+                    after_quantifier = true;
+                }
+                i += 1;
+            }
+            b'{' => {
+                let Some(length) = bytes[i..].iter().position(|&b| b == b'}') else {
+                    i += 1;
+                    continue;
+                };
+                let body = &bytes[i + 1..i + length];
+                if body.iter().any(|b| b"eids".contains(b)) {
+                    found(Construct::Fuzzy, start);
+                } else if !body.is_empty() && body.iter().all(|&b| b.is_ascii_digit() || b == b',')
+                {
+                    after_quantifier = true;
+                }
+                i += length + 1;
+            }
+            b => i += utf8_len(b),
+        }
+    }
+    PatternReport { findings }
+}
+
+/// Returns the index after the first `delimiter` at or after `from`, or the end of `bytes`.
+fn skip_past(bytes: &[u8], from: usize, delimiter: u8) -> usize {
+    bytes[from..]
+        .iter()
+        .position(|&b| b == delimiter)
+        .map_or(bytes.len(), |position| from + position + 1)
+}
+
+/// Returns the index after the character class starting at `from`. A `]` right after the
+/// opening `[` or `[^` is a literal.
+fn skip_class(bytes: &[u8], from: usize) -> usize {
+    let mut i = from + 1;
+    if bytes.get(i) == Some(&b'^') {
+        i += 1;
+    }
+    if bytes.get(i) == Some(&b']') {
+        i += 1;
+    }
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b']' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns `true` for `0`, `1`, `+1`, `-2` and so on, as in `(?1)`.
+fn is_group_number(text: &[u8]) -> bool {
+    let digits = text
+        .strip_prefix(b"+")
+        .or(text.strip_prefix(b"-"))
+        .unwrap_or(text);
+    !digits.is_empty() && digits.iter().all(u8::is_ascii_digit)
+}
+
+/// Returns the length of the UTF-8 sequence starting with `byte`.
+fn utf8_len(byte: u8) -> usize {
+    match byte {
+        0xf0.. => 4,
+        0xe0.. => 3,
+        0xc0.. => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze() {
+        let constructs = |pattern| {
+            analyze(pattern)
+                .findings
+                .into_iter()
+                .map(|finding| finding.construct)
+                .collect::<Vec<_>>()
+        };
+        assert!(analyze(r"^[\w.+-]+@\w+\.\w{2,}$").is_portable());
+        assert!(analyze(r"[(?=\1][]{e}] \p{Greek} \x{263a} \101 (?#(?=)").is_portable());
+        assert_eq!(
+            constructs(r"(a)\1 (?P<n>b)(?P=n) \g<n>"),
+            [Construct::Backreference; 3]
+        );
+        assert_eq!(
+            constructs(r"(?=a)(?!b)(?<=c)(?<!d)"),
+            [
+                Construct::Lookahead,
+                Construct::Lookahead,
+                Construct::Lookbehind,
+                Construct::Lookbehind
+            ]
+        );
+        assert_eq!(
+            constructs(r"\((?:[^()]|(?R))*\) (?1) (?&x)"),
+            [Construct::Recursion; 3]
+        );
+        assert_eq!(
+            constructs(r"(?:cat){e<=1} a++b{2}+ (?>x)"),
+            [
+                Construct::Fuzzy,
+                Construct::PossessiveQuantifier,
+                Construct::PossessiveQuantifier,
+                Construct::AtomicGroup
+            ]
+        );
+        assert_eq!(analyze(r"ab(?=c)").findings[0].offset, 2);
+    }
+}
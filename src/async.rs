@@ -0,0 +1,174 @@
+//! Async versions of the matching methods for tokio applications.
+//!
+//! Matching holds the GIL and blocks the calling thread, which stalls the reactor when done from
+//! an async task. These methods run the blocking call on tokio's blocking thread pool instead:
+//!
+//! ```
+//! # async fn run() -> pyo3::PyResult<()> {
+//! let re = py_regex::PyRegex::new(r"\d+")?;
+//! let replaced = re.replace_async("a1 b22", "N").await?;
+//! assert_eq!(replaced, "aN bN");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! `MatchStream` searches a line-oriented `AsyncRead` source, such as a tailed file or a socket,
+//! and yields the matches as a `Stream`.
+
+use crate::match_data::MatchData;
+use crate::python::{PyRegex, PyRegexMatch};
+use futures_core::Stream;
+use pyo3::PyResult;
+use pyo3::exceptions::PyRuntimeError;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, BufReader, Lines};
+
+impl PyRegex {
+    /// Same as `search_match`, run on the blocking thread pool.
+    pub async fn search_match_async(&self, text: &str) -> PyResult<Option<PyRegexMatch>> {
+        let (re, text) = (self.clone(), text.to_string());
+        blocking(move || re.search_match(&text)).await
+    }
+
+    /// Same as `find_iter`, run on the blocking thread pool.
+    pub async fn find_iter_async(&self, text: &str) -> PyResult<Vec<PyRegexMatch>> {
+        let (re, text) = (self.clone(), text.to_string());
+        blocking(move || re.find_iter(&text)).await
+    }
+
+    /// Same as `replace`, run on the blocking thread pool.
+    pub async fn replace_async(&self, text: &str, replacement: &str) -> PyResult<String> {
+        let (re, text, replacement) = (self.clone(), text.to_string(), replacement.to_string());
+        blocking(move || re.replace(&text, &replacement)).await
+    }
+}
+
+type Search = Pin<Box<dyn Future<Output = PyResult<Vec<MatchData>>> + Send>>;
+
+/// A `Stream` of the matches of a pattern in a source read line by line.
+///
+/// Lines are split on `\n` (with a trailing `\r` removed) and searched one at a time on the
+/// blocking thread pool, so a match never spans lines and the positions in each `MatchData` are
+/// relative to its line. A read error or a failed search is yielded as an item.
+pub struct MatchStream<R> {
+    re: PyRegex,
+    lines: Lines<R>,
+    search: Option<Search>,
+    pending: VecDeque<MatchData>,
+}
+
+impl<R: AsyncRead + Unpin> MatchStream<BufReader<R>> {
+    /// Creates a stream over the matches of `re` in `reader`, buffering it to split lines.
+    pub fn new(re: PyRegex, reader: R) -> Self {
+        Self::from_lines(re, BufReader::new(reader).lines())
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> MatchStream<R> {
+    /// Creates a stream over the matches of `re` in already split lines.
+    pub fn from_lines(re: PyRegex, lines: Lines<R>) -> Self {
+        MatchStream {
+            re,
+            lines,
+            search: None,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for MatchStream<R> {
+    type Item = PyResult<MatchData>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(data) = this.pending.pop_front() {
+                return Poll::Ready(Some(Ok(data)));
+            }
+            if let Some(search) = &mut this.search {
+                let result = ready!(search.as_mut().poll(cx));
+                this.search = None;
+                match result {
+                    Ok(matches) => this.pending.extend(matches),
+                    Err(err) => return Poll::Ready(Some(Err(err))),
+                }
+                continue;
+            }
+            match ready!(Pin::new(&mut this.lines).poll_next_line(cx)) {
+                Ok(Some(line)) => {
+                    if let Err(err) = this.re.check_cancelled() {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    let re = this.re.clone();
+                    this.search = Some(Box::pin(blocking(move || {
+                        re.find_iter(&line)?.iter().map(|m| m.to_data()).collect()
+                    })));
+                }
+                Ok(None) => return Poll::Ready(None),
+                Err(err) => return Poll::Ready(Some(Err(err.into()))),
+            }
+        }
+    }
+}
+
+/// Runs `f` with `spawn_blocking`, resuming its panic if it panicked.
+async fn blocking<T, F>(f: F) -> PyResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> PyResult<T> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => Err(PyRuntimeError::new_err(format!(
+            "blocking match task failed: {err}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_async_methods() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let re = PyRegex::new(r"(\w+)@(\w+)")?;
+            let m = re.search_match_async("mail user@host").await?.unwrap();
+            assert_eq!(m.group(1)?.as_deref(), Some("user"));
+            assert_eq!(re.find_iter_async("a@b c@d").await?.len(), 2);
+            assert_eq!(re.replace_async("a@b", r"\2@\1").await?, "b@a");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_match_stream() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let re = PyRegex::new(r"(\w+)@(\w+)")?;
+            let source: &[u8] = b"a@b x\r\nnone\nc@d e@f";
+            let mut stream = MatchStream::new(re, source);
+            let mut found = Vec::new();
+            while let Some(data) =
+                std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await
+            {
+                found.push(data?[0].to_string());
+            }
+            assert_eq!(found, ["a@b", "c@d", "e@f"]);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,208 @@
+use crate::flags::Flags;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyImportError, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyModule};
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The Python module that compiles and runs the patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// The third-party `regex` module.
+    Regex,
+    /// The standard library `re` module, used when `regex` is not installed and the fallback is enabled.
+    Re,
+}
+
+/// Capabilities of the `regex` module that may be unavailable on the active backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Feature {
+    /// Approximate matching such as `(?:rust){e<=2}`.
+    Fuzzy,
+    /// Releasing the GIL while matching (`concurrent=True`). Not used with the `pypy` feature,
+    /// where the `regex` extension runs through PyPy's C API emulation layer.
+    Concurrent,
+    /// The `special_only` and `literal_spaces` options of `escape`.
+    EscapeOptions,
+    /// Aborting matches that run longer than a timeout.
+    Timeout,
+    /// VERSION1 behaviour (nested sets, set operations, scoped inline flags).
+    Version1,
+    /// Named lists of alternatives (`\L<name>`) supplied at compile time.
+    NamedLists,
+    /// Partial matches at the end of the text (`partial=True`), needed to match streamed input.
+    Partial,
+    /// Unicode property classes such as `\p{Script=Greek}`.
+    UnicodeProperties,
+    /// Flags that only the `regex` module defines, such as `POSIX`.
+    Flag(Flags),
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Feature::Fuzzy => "fuzzy matching",
+            Feature::Concurrent => "concurrent matching",
+            Feature::EscapeOptions => "escape options",
+            Feature::Timeout => "match timeouts",
+            Feature::Version1 => "VERSION1 behaviour",
+            Feature::NamedLists => "named lists",
+            Feature::Partial => "partial matching",
+            Feature::UnicodeProperties => "Unicode property classes",
+            Feature::Flag(flags) => return write!(f, "the {flags:?} flag"),
+        })
+    }
+}
+
+create_exception!(
+    py_regex,
+    UnsupportedFeature,
+    PyException,
+    "Raised when a feature is not available with the installed Python module."
+);
+
+impl Backend {
+    /// Returns the name of the Python module.
+    pub fn module_name(self) -> &'static str {
+        match self {
+            Backend::Regex => "regex",
+            Backend::Re => "re",
+        }
+    }
+
+    /// Returns `true` if the backend supports the given feature.
+    pub fn supports(self, feature: Feature) -> bool {
+        match self {
+            Backend::Regex => !(cfg!(feature = "pypy") && feature == Feature::Concurrent),
+            Backend::Re => match feature {
+                Feature::Fuzzy
+                | Feature::Concurrent
+                | Feature::EscapeOptions
+                | Feature::Timeout
+                | Feature::Version1
+                | Feature::NamedLists
+                | Feature::Partial
+                | Feature::UnicodeProperties
+                | Feature::Flag(_) => false,
+            },
+        }
+    }
+}
+
+/// Versions and capabilities of the Python side, as detected on first use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendInfo {
+    /// The active backend.
+    pub backend: Backend,
+    /// The `__version__` of the backend module, if it has one.
+    pub module_version: Option<String>,
+    /// The version of the embedded Python interpreter.
+    pub python_version: String,
+    timeout: bool,
+    version1: bool,
+}
+
+impl BackendInfo {
+    /// Returns `true` if the installed module supports the given feature.
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.backend.supports(feature)
+            && match feature {
+                Feature::Timeout => self.timeout,
+                Feature::Version1 => self.version1,
+                _ => true,
+            }
+    }
+}
+
+static RE_FALLBACK: AtomicBool = AtomicBool::new(false);
+static BACKEND: OnceLock<Backend> = OnceLock::new();
+static INFO: OnceLock<BackendInfo> = OnceLock::new();
+
+/// Allows falling back to the standard library `re` module when `regex` cannot be imported.
+/// The backend is resolved once, so this must be called before the first pattern is compiled.
+pub fn set_re_fallback(enabled: bool) {
+    RE_FALLBACK.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns the active backend, resolving it on first use.
+pub fn backend() -> PyResult<Backend> {
+    Python::with_gil(|py| module(py).map(|_| *BACKEND.get().unwrap()))
+}
+
+/// Returns the versions and capabilities of the active backend.
+pub fn backend_info() -> PyResult<BackendInfo> {
+    Python::with_gil(|py| info(py).cloned())
+}
+
+/// Detects the capabilities of the active backend once.
+pub(crate) fn info(py: Python) -> PyResult<&'static BackendInfo> {
+    if let Some(info) = INFO.get() {
+        return Ok(info);
+    }
+    let module = module(py)?;
+    // Older `regex` releases reject the `timeout` argument.
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("timeout", 1.0)?;
+    let timeout =
+        match module
+            .call_method1("compile", ("",))?
+            .call_method("search", ("",), Some(&kwargs))
+        {
+            Ok(_) => true,
+            Err(err) if err.is_instance_of::<PyTypeError>(py) => false,
+            Err(err) => return Err(err),
+        };
+    let info = BackendInfo {
+        backend: active(),
+        module_version: module.getattr("__version__").ok().map(|v| v.to_string()),
+        python_version: py
+            .import("platform")?
+            .call_method0("python_version")?
+            .extract()?,
+        timeout,
+        version1: module.hasattr("VERSION1")?,
+    };
+    Ok(INFO.get_or_init(|| info))
+}
+
+/// Returns an `UnsupportedFeature` error unless the active backend supports `feature`.
+pub(crate) fn require(py: Python, feature: Feature) -> PyResult<()> {
+    let info = info(py)?;
+    if info.supports(feature) {
+        return Ok(());
+    }
+    Err(UnsupportedFeature::new_err(format!(
+        "the `{}` module{} does not support {feature}",
+        info.backend.module_name(),
+        info.module_version
+            .as_ref()
+            .map(|version| format!(" {version}"))
+            .unwrap_or_default()
+    )))
+}
+
+/// Imports the module of the active backend.
+pub(crate) fn module(py: Python) -> PyResult<Bound<PyModule>> {
+    if let Some(backend) = BACKEND.get() {
+        return PyModule::import(py, backend.module_name());
+    }
+    let (backend, module) = match PyModule::import(py, "regex") {
+        Ok(module) => (Backend::Regex, module),
+        Err(err)
+            if err.is_instance_of::<PyImportError>(py) && RE_FALLBACK.load(Ordering::Relaxed) =>
+        {
+            (Backend::Re, PyModule::import(py, "re")?)
+        }
+        Err(err) => return Err(err),
+    };
+    let _ = BACKEND.set(backend);
+    Ok(module)
+}
+
+/// Returns the active backend, given that `module` has already succeeded.
+pub(crate) fn active() -> Backend {
+    BACKEND.get().copied().unwrap_or(Backend::Regex)
+}
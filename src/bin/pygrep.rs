@@ -0,0 +1,178 @@
+//! `pygrep`: search files with the Python `regex` dialect.
+
+use py_regex::pyo3::exceptions::PyOSError;
+use py_regex::{Flags, PyRegex, PyResult};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::ExitCode;
+use std::{env, fs};
+
+const USAGE: &str = "\
+Usage: pygrep [OPTIONS] PATTERN [FILE]...
+
+Searches each FILE (or standard input) for lines matching PATTERN, using the
+syntax of the Python `regex` module.
+
+Options:
+  -o, --only-matching    print only the matched parts of each line
+  -c, --count            print only the number of matching lines per file
+  -n, --line-number      prefix each line with its line number
+  -i, --ignore-case      match case-insensitively
+  -r, --replace TEXT     print lines with matches replaced by TEXT (`\\1`, `\\g<name>`)
+  -e, --max-errors N     allow up to N errors (fuzzy matching)
+  -b, --best-match       report the best fuzzy match instead of the first one
+  -h, --help             print this help";
+
+#[derive(Default)]
+struct Options {
+    only_matching: bool,
+    count: bool,
+    line_number: bool,
+    flags: Flags,
+    replace: Option<String>,
+    max_errors: Option<u32>,
+    pattern: String,
+    files: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut pattern = None;
+    let mut positional_only = false;
+    while let Some(arg) = args.next() {
+        if positional_only || !arg.starts_with('-') || arg == "-" {
+            if pattern.is_none() {
+                pattern = Some(arg);
+            } else {
+                options.files.push(arg);
+            }
+            continue;
+        }
+        let mut value = |name: &str| args.next().ok_or(format!("{name} requires a value"));
+        match arg.as_str() {
+            "--" => positional_only = true,
+            "-o" | "--only-matching" => options.only_matching = true,
+            "-c" | "--count" => options.count = true,
+            "-n" | "--line-number" => options.line_number = true,
+            "-i" | "--ignore-case" => options.flags |= Flags::IGNORECASE,
+            "-b" | "--best-match" => options.flags |= Flags::BESTMATCH,
+            "-r" | "--replace" => options.replace = Some(value(&arg)?),
+            "-e" | "--max-errors" => {
+                let errors = value(&arg)?;
+                options.max_errors = Some(
+                    errors
+                        .parse()
+                        .map_err(|_| format!("invalid number of errors: {errors}"))?,
+                );
+            }
+            "-h" | "--help" => return Err(String::new()),
+            _ => return Err(format!("unknown option: {arg}")),
+        }
+    }
+    options.pattern = pattern.ok_or("missing PATTERN")?;
+    Ok(options)
+}
+
+/// Searches one input, returning whether any line matched.
+fn grep(
+    re: &PyRegex,
+    options: &Options,
+    name: Option<&str>,
+    input: impl BufRead,
+    out: &mut impl Write,
+) -> PyResult<bool> {
+    let prefix = |line_number: usize| {
+        let mut prefix = String::new();
+        if let Some(name) = name {
+            prefix.push_str(name);
+            prefix.push(':');
+        }
+        if options.line_number {
+            prefix.push_str(&format!("{line_number}:"));
+        }
+        prefix
+    };
+    let mut count = 0;
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        let matches = re.find_iter(&line)?;
+        if matches.is_empty() {
+            continue;
+        }
+        count += 1;
+        if options.count {
+            continue;
+        }
+        let prefix = prefix(index + 1);
+        if options.only_matching {
+            for m in matches {
+                let text = match &options.replace {
+                    Some(replacement) => m.expand(replacement)?,
+                    None => m.group(0)?.unwrap_or_default(),
+                };
+                writeln!(out, "{prefix}{text}")?;
+            }
+        } else if let Some(replacement) = &options.replace {
+            writeln!(out, "{prefix}{}", re.replace(&line, replacement)?)?;
+        } else {
+            writeln!(out, "{prefix}{line}")?;
+        }
+    }
+    if options.count {
+        writeln!(
+            out,
+            "{}{count}",
+            name.map(|name| format!("{name}:")).unwrap_or_default()
+        )?;
+    }
+    Ok(count > 0)
+}
+
+fn run(options: &Options) -> PyResult<bool> {
+    let pattern = match options.max_errors {
+        Some(errors) => format!("(?:{}){{e<={errors}}}", options.pattern),
+        None => options.pattern.clone(),
+    };
+    let re = PyRegex::with_flags(&pattern, options.flags)?;
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut found = false;
+    if options.files.is_empty() {
+        found = grep(&re, options, None, io::stdin().lock(), &mut out)?;
+    }
+    let name_files = options.files.len() > 1;
+    for file in &options.files {
+        let name = name_files.then_some(file.as_str());
+        found |= if file == "-" {
+            grep(&re, options, name, io::stdin().lock(), &mut out)?
+        } else {
+            let input =
+                fs::File::open(file).map_err(|err| PyOSError::new_err(format!("{file}: {err}")))?;
+            let input = BufReader::new(input);
+            grep(&re, options, name, input, &mut out)?
+        };
+    }
+    out.flush()?;
+    Ok(found)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) if message.is_empty() => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("pygrep: {message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("pygrep: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::python::PyRegex;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyTimeoutError};
+use pyo3::prelude::*;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+create_exception!(
+    py_regex,
+    CircuitOpen,
+    PyException,
+    "Raised instead of matching when a circuit breaker has stopped calls to its pattern."
+);
+
+/// Recent calls, `true` for the ones that timed out or were slow.
+#[derive(Debug, Default)]
+struct Window {
+    calls: VecDeque<bool>,
+    failures: usize,
+    open: bool,
+}
+
+/// A pattern that stops matching once too many recent calls timed out or ran slowly, so that one
+/// pathological rule cannot tie up a matching service.
+///
+/// Once at least `failures` of the last `window` calls failed (5 of 20 by default), the breaker
+/// opens and every call returns a `CircuitOpen` error without running the pattern, until `reset`
+/// is called. A call fails if it raises a Python `TimeoutError`, see `PyRegex::with_timeout`, or
+/// takes at least the slow call threshold set with `with_slow_call`. The lock is not held while
+/// Python runs.
+///
+/// ```
+/// use py_regex::{CircuitBreaker, CircuitOpen, PyRegex};
+/// use std::time::Duration;
+///
+/// let re = PyRegex::new(r"^(\w+\s?)*$")?.with_timeout(Duration::from_millis(50))?;
+/// let rule = CircuitBreaker::new(re).with_slow_call(Duration::from_millis(10));
+/// match rule.is_match("some user input") {
+///     Err(err) if pyo3::Python::with_gil(|py| err.is_instance_of::<CircuitOpen>(py)) => {
+///         // The rule is disabled until `rule.reset()`.
+///     }
+///     result => println!("{}", result?),
+/// }
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+pub struct CircuitBreaker {
+    re: PyRegex,
+    slow_call: Option<Duration>,
+    failures: usize,
+    window: usize,
+    state: Mutex<Window>,
+}
+
+impl CircuitBreaker {
+    /// Wraps the pattern with a closed breaker that opens after 5 failures in 20 calls.
+    pub fn new(re: PyRegex) -> Self {
+        CircuitBreaker {
+            re,
+            slow_call: None,
+            failures: 5,
+            window: 20,
+            state: Mutex::new(Window::default()),
+        }
+    }
+
+    /// Counts calls that take at least `threshold` as failures.
+    pub fn with_slow_call(mut self, threshold: Duration) -> Self {
+        self.slow_call = Some(threshold);
+        self
+    }
+
+    /// Opens the breaker once at least `failures` of the last `window` calls failed.
+    pub fn with_threshold(mut self, failures: usize, window: usize) -> Self {
+        self.failures = failures.max(1);
+        self.window = window.max(self.failures);
+        self
+    }
+
+    /// Returns the wrapped pattern.
+    pub fn regex(&self) -> &PyRegex {
+        &self.re
+    }
+
+    /// Returns `true` if calls are being short-circuited.
+    pub fn is_open(&self) -> bool {
+        self.state.lock().unwrap().open
+    }
+
+    /// Closes the breaker and forgets the recent calls.
+    pub fn reset(&self) {
+        *self.state.lock().unwrap() = Window::default();
+    }
+
+    /// Same as `PyRegex::is_match`, unless the breaker is open.
+    pub fn is_match(&self, text: &str) -> PyResult<bool> {
+        self.call(|re| re.is_match(text))
+    }
+
+    /// Same as `PyRegex::find_all`, unless the breaker is open.
+    pub fn find_all(&self, text: &str) -> PyResult<Vec<String>> {
+        self.call(|re| re.find_all(text))
+    }
+
+    /// Runs `f` with the pattern and records how it went, or returns a `CircuitOpen` error
+    /// without running it if the breaker is open.
+    pub fn call<T>(&self, f: impl FnOnce(&PyRegex) -> PyResult<T>) -> PyResult<T> {
+        if self.is_open() {
+            return Err(CircuitOpen::new_err(format!(
+                "too many slow or timed out calls to pattern `{}`",
+                self.re.pattern().unwrap_or_default()
+            )));
+        }
+        let start = Instant::now();
+        let result = f(&self.re);
+        let failed = match &result {
+            Err(err) => Python::with_gil(|py| err.is_instance_of::<PyTimeoutError>(py)),
+            Ok(_) => false,
+        } || self
+            .slow_call
+            .is_some_and(|threshold| start.elapsed() >= threshold);
+
+        let mut state = self.state.lock().unwrap();
+        state.calls.push_back(failed);
+        state.failures += usize::from(failed);
+        if state.calls.len() > self.window {
+            let oldest = state.calls.pop_front().expect("window is not empty");
+            state.failures -= usize::from(oldest);
+        }
+        if state.failures >= self.failures {
+            state.open = true;
+        }
+        result
+    }
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("re", &self.re)
+            .field("slow_call", &self.slow_call)
+            .field("failures", &self.failures)
+            .field("window", &self.window)
+            .field("open", &self.is_open())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        // Every call counts as slow.
+        let rule = CircuitBreaker::new(PyRegex::new(r"\d+")?)
+            .with_slow_call(Duration::ZERO)
+            .with_threshold(2, 3);
+        assert!(rule.is_match("1")?);
+        assert_eq!(rule.find_all("1 2")?, ["1", "2"]);
+        assert!(rule.is_open());
+        let err = rule.is_match("1").unwrap_err();
+        assert!(Python::with_gil(|py| err.is_instance_of::<CircuitOpen>(py)));
+
+        rule.reset();
+        assert!(!rule.is_open());
+        assert!(rule.is_match("1")?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::flags::{Flags, Version};
+use crate::python::PyRegex;
+use pyo3::PyResult;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Builds a `PyRegex` with compile-time options beyond flags, created by `PyRegex::builder`.
+///
+/// ```
+/// use py_regex::PyRegex;
+///
+/// let re = PyRegex::builder(r"\b\L<fruits>\b")
+///     .named_list("fruits", ["apple", "banana", "cherry"])
+///     .build()?;
+/// assert!(re.is_match("a banana split")?);
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct PyRegexBuilder {
+    pattern: String,
+    flags: Flags,
+    named_lists: HashMap<String, Vec<String>>,
+    timeout: Option<Duration>,
+    version: Option<Version>,
+}
+
+impl PyRegexBuilder {
+    /// Creates a builder for the pattern without flags.
+    pub fn new(pattern: &str) -> Self {
+        PyRegexBuilder {
+            pattern: pattern.to_string(),
+            flags: Flags::NONE,
+            named_lists: HashMap::new(),
+            timeout: None,
+            version: None,
+        }
+    }
+
+    /// Sets the flags to compile with.
+    pub fn flags(mut self, flags: Flags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Selects the behaviour version instead of relying on the module default.
+    pub fn version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Attaches a named list, matched by `\L<name>` in the pattern as any one of `items`.
+    /// Replaces a list previously attached under the same name.
+    pub fn named_list<I>(mut self, name: &str, items: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.named_lists.insert(
+            name.to_string(),
+            items.into_iter().map(Into::into).collect(),
+        );
+        self
+    }
+
+    /// Sets a timeout for matching operations, as with `PyRegex::with_timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Compiles the pattern. Named lists require the `regex` backend, and the `regex` module
+    /// rejects lists that the pattern does not use.
+    pub fn build(&self) -> PyResult<PyRegex> {
+        let mut flags = self.flags;
+        if let Some(version) = self.version {
+            flags = flags.difference(Flags::VERSION0 | Flags::VERSION1) | version.flags();
+        }
+        let re = PyRegex::compile(&self.pattern, flags, &self.named_lists)?;
+        match self.timeout {
+            Some(timeout) => re.with_timeout(timeout),
+            None => Ok(re),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Byte-string results for `BStr` haystacks, which are searched as text decoded with Python's
+//! `surrogateescape`, so that invalid UTF-8 in the input is returned as it was.
+
+use crate::haystack::PyText;
+use crate::python::{PyRegex, PyRegexMatch};
+use crate::{Group, PyResult};
+use bstr::{BStr, BString};
+use pyo3::prelude::*;
+
+/// Encodes a Python `str` from a `BStr` haystack back into the original bytes.
+fn encode(text: &Bound<PyAny>) -> PyResult<BString> {
+    Ok(text
+        .call_method1("encode", ("utf-8", "surrogateescape"))?
+        .extract::<Vec<u8>>()?
+        .into())
+}
+
+impl PyRegex {
+    /// Same as `replace` for a byte string, keeping invalid UTF-8 in the text as it is.
+    pub fn replace_bytes(&self, text: &BStr, replacement: impl AsRef<str>) -> PyResult<BString> {
+        let operation = self.operation("sub", text);
+        operation.finish(Python::with_gil(|py| {
+            encode(&self.as_py().bind(py).call_method(
+                "sub",
+                (replacement.as_ref(), text.to_py_str(py)),
+                self.kwargs(py).as_ref(),
+            )?)
+        }))
+    }
+
+    /// Same as `find_all` for a byte string, returning the matched bytes.
+    pub fn find_all_bytes(&self, text: &BStr) -> PyResult<Vec<BString>> {
+        let operation = self.operation("findall", text);
+        let result = Python::with_gil(|py| {
+            self.as_py()
+                .bind(py)
+                .call_method("findall", (text.to_py_str(py),), self.kwargs(py).as_ref())?
+                .try_iter()?
+                .map(|found| encode(&found?))
+                .collect()
+        });
+        operation.finish_counting(result, Vec::len)
+    }
+}
+
+impl PyRegexMatch {
+    /// Returns the bytes of the specified group of a match in a `BStr` haystack, or `None` if
+    /// the group did not participate.
+    pub fn group_bytes<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<Option<BString>> {
+        Python::with_gil(|py| {
+            let found = self.as_py().call_method1(py, "group", (group.into(),))?;
+            Ok(if found.is_none(py) {
+                None
+            } else {
+                Some(encode(found.bind(py))?)
+            })
+        })
+    }
+}
@@ -0,0 +1,135 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+create_exception!(
+    py_regex,
+    Cancelled,
+    PyException,
+    "Raised by batch and streaming operations whose cancellation token was cancelled."
+);
+
+/// A flag shared between a long-running matching job and whatever decides to stop it, such as a
+/// shutdown handler. Clones refer to the same flag.
+///
+/// Attach it to a pattern with `PyRegex::with_cancellation`. Column functions, stream splitting
+/// and replacement, `IncrementalMatcher`, `RecordSplitter` and `MatchStream` check it before each
+/// item and fail with a `Cancelled` error once it is set, as do `Pipeline::run` and the jobs of a
+/// `WorkerRegex`, which take their own token. Operations on a single text, such as `find_iter`
+/// or `Lexer::tokenize`, do not check it, and a single call into Python is not interrupted;
+/// bound those with `PyRegex::with_timeout`.
+///
+/// ```
+/// use py_regex::{CancellationToken, PyRegex};
+///
+/// let token = CancellationToken::new();
+/// let re = PyRegex::new(r"\n")?.with_cancellation(token.clone());
+/// let mut pieces = re.split_stream("a\nb\nc".as_bytes());
+/// assert_eq!(pieces.next().unwrap()?, "a");
+/// token.cancel();
+/// assert!(pieces.next().unwrap().is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every operation checking this token or one of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` once `cancel` has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns a `Cancelled` error if the token was cancelled.
+    pub(crate) fn check(&self) -> PyResult<()> {
+        match self.is_cancelled() {
+            true => Err(Cancelled::new_err("the operation was cancelled")),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worker::Worker;
+    use crate::{Pipeline, PyRegex, RecordSplitter};
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn test_cancellation() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let token = CancellationToken::new();
+        let re = PyRegex::new(r"\d")?.with_cancellation(token.clone());
+        let mut output = Vec::new();
+        assert_eq!(re.replace_stream("a1b2".as_bytes(), &mut output, "#")?, 2);
+
+        let mut records = RecordSplitter::new("^#")?
+            .with_cancellation(token.clone())
+            .records("#a\n#b\n#c\n".as_bytes());
+        assert_eq!(records.next().transpose()?.as_deref(), Some("#a"));
+        token.cancel();
+        let err = records.next().unwrap().unwrap_err();
+        assert!(Python::with_gil(|py| err.is_instance_of::<Cancelled>(py)));
+        assert!(
+            re.replace_stream("a1".as_bytes(), &mut output, "#")
+                .is_err()
+        );
+        // Single calls are not affected.
+        assert!(re.is_match("1")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancellation_worker_pipeline() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let token = CancellationToken::new();
+        let pipeline = Pipeline::new()
+            .stage(r"\w+", 0)?
+            .with_cancellation(token.clone());
+        assert_eq!(pipeline.run("a b")?, ["a", "b"]);
+        let worker = Worker::new()?;
+        let re = worker.compile(r"\d")?.with_cancellation(token.clone());
+        assert!(re.is_match("1")?);
+        // Keep the worker busy so that the submitted job is still queued when the token is
+        // cancelled.
+        let (started, running) = mpsc::channel();
+        let (release, blocked) = mpsc::channel::<()>();
+        let busy = worker.clone();
+        let busy = thread::spawn(move || {
+            busy.run(move || {
+                started.send(()).unwrap();
+                Ok(blocked.recv().ok())
+            })
+        });
+        running.recv().unwrap();
+        let queued = re.submit("1")?;
+        token.cancel();
+        release.send(()).unwrap();
+        busy.join().unwrap()?;
+
+        let cancelled = |err: PyErr| Python::with_gil(|py| err.is_instance_of::<Cancelled>(py));
+        assert!(cancelled(queued.recv().unwrap().unwrap_err()));
+        assert!(cancelled(re.is_match("1").unwrap_err()));
+        assert!(cancelled(pipeline.run("a b").unwrap_err()));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,470 @@
+//! A facade mirroring the API of the `regex` crate, backed by the Python `regex` module.
+//!
+//! `Regex`, `Match` and `Captures` behave like their `regex` crate namesakes: positions are byte
+//! offsets into the haystack and replacement templates use the `$1`/`${name}` syntax. Code written
+//! against the `regex` crate can switch to the Python engine's semantics (backreferences,
+//! lookbehind, fuzzy matching) by changing its imports.
+//!
+//! Matching methods cannot fail in the `regex` crate, so an exception raised by Python while
+//! matching (such as a timeout) panics here.
+
+use crate::PyRegex;
+use crate::offsets::ByteOffsets;
+use pyo3::prelude::*;
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::{Index, Range};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A compiled regular expression with the API of `regex::Regex`.
+#[derive(Clone)]
+pub struct Regex {
+    inner: PyRegex,
+    pattern: String,
+    /// Group names by group number, including group 0.
+    names: Arc<[Option<String>]>,
+}
+
+impl Regex {
+    /// Compiles a regular expression.
+    pub fn new(re: &str) -> PyResult<Regex> {
+        let inner = PyRegex::new(re)?;
+        let mut names = vec![None; inner.groups_len()? + 1];
+        for (name, index) in inner.group_index()? {
+            names[index] = Some(name);
+        }
+        Ok(Regex {
+            inner,
+            pattern: re.to_string(),
+            names: names.into(),
+        })
+    }
+
+    /// Returns the original pattern string.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns `true` if the regex matches anywhere in the haystack.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.inner
+            .is_match(haystack)
+            .unwrap_or_else(|err| self.fail(err))
+    }
+
+    /// Returns the leftmost-first match in the haystack.
+    pub fn find<'h>(&self, haystack: &'h str) -> Option<Match<'h>> {
+        self.captures(haystack).map(|caps| caps.get_match())
+    }
+
+    /// Returns an iterator over all non-overlapping matches.
+    pub fn find_iter<'h>(&self, haystack: &'h str) -> impl Iterator<Item = Match<'h>> + use<'h> {
+        self.captures_list(haystack, None)
+            .into_iter()
+            .map(|caps| caps.get_match())
+    }
+
+    /// Returns the capture groups of the leftmost-first match.
+    pub fn captures<'h>(&self, haystack: &'h str) -> Option<Captures<'h>> {
+        self.captures_list(haystack, Some(1)).into_iter().next()
+    }
+
+    /// Returns an iterator over the capture groups of all non-overlapping matches.
+    pub fn captures_iter<'h>(
+        &self,
+        haystack: &'h str,
+    ) -> impl Iterator<Item = Captures<'h>> + use<'h> {
+        self.captures_list(haystack, None).into_iter()
+    }
+
+    /// Returns an iterator over the substrings between matches.
+    pub fn split<'h>(&self, haystack: &'h str) -> impl Iterator<Item = &'h str> + use<'h> {
+        self.splitn(haystack, usize::MAX)
+    }
+
+    /// Returns an iterator over at most `limit` substrings between matches; the last one
+    /// contains the remainder of the haystack.
+    pub fn splitn<'h>(
+        &self,
+        haystack: &'h str,
+        limit: usize,
+    ) -> impl Iterator<Item = &'h str> + use<'h> {
+        let mut parts = Vec::new();
+        if limit > 0 {
+            let mut last = 0;
+            for m in self.find_iter(haystack).take(limit - 1) {
+                parts.push(&haystack[last..m.start()]);
+                last = m.end();
+            }
+            parts.push(&haystack[last..]);
+        }
+        parts.into_iter()
+    }
+
+    /// Replaces the first match. `rep` is a `$name`/`${name}` template, a `NoExpand` literal or a
+    /// closure computing the replacement from the `Captures`.
+    pub fn replace<'h, R: Replacer>(&self, haystack: &'h str, rep: R) -> Cow<'h, str> {
+        self.replacen(haystack, 1, rep)
+    }
+
+    /// Replaces all matches; see `replace`.
+    pub fn replace_all<'h, R: Replacer>(&self, haystack: &'h str, rep: R) -> Cow<'h, str> {
+        self.replacen(haystack, 0, rep)
+    }
+
+    /// Replaces at most `limit` matches (all of them if `limit` is 0); see `replace`.
+    pub fn replacen<'h, R: Replacer>(
+        &self,
+        haystack: &'h str,
+        limit: usize,
+        mut rep: R,
+    ) -> Cow<'h, str> {
+        let captures = self.captures_list(haystack, (limit > 0).then_some(limit));
+        if captures.is_empty() {
+            return Cow::Borrowed(haystack);
+        }
+        let mut result = String::with_capacity(haystack.len());
+        let mut last = 0;
+        for caps in &captures {
+            let m = caps.get_match();
+            result.push_str(&haystack[last..m.start()]);
+            match rep.no_expansion() {
+                Some(literal) => result.push_str(&literal),
+                None => rep.replace_append(caps, &mut result),
+            }
+            last = m.end();
+        }
+        result.push_str(&haystack[last..]);
+        Cow::Owned(result)
+    }
+
+    /// Returns the number of groups, including the implicit group 0.
+    pub fn captures_len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns the names of all groups by group number, `None` for unnamed ones.
+    pub fn capture_names(&self) -> impl Iterator<Item = Option<&str>> {
+        self.names.iter().map(|name| name.as_deref())
+    }
+
+    /// Runs the search in Python and converts the spans to byte offsets into the haystack.
+    fn captures_list<'h>(&self, haystack: &'h str, limit: Option<usize>) -> Vec<Captures<'h>> {
+        let regs = self
+            .inner
+            .find_regs(haystack, limit)
+            .unwrap_or_else(|err| self.fail(err));
+        let offsets = ByteOffsets::new(haystack);
+        regs.into_iter()
+            .map(|regs| Captures {
+                haystack,
+                spans: regs
+                    .into_iter()
+                    .map(|(start, end)| {
+                        (start >= 0).then(|| offsets.get(start as usize)..offsets.get(end as usize))
+                    })
+                    .collect(),
+                names: Arc::clone(&self.names),
+            })
+            .collect()
+    }
+
+    #[track_caller]
+    fn fail(&self, err: PyErr) -> ! {
+        panic!("matching `{}` failed in Python: {err}", self.pattern)
+    }
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Regex").field(&self.pattern).finish()
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl FromStr for Regex {
+    type Err = PyErr;
+
+    fn from_str(s: &str) -> PyResult<Regex> {
+        Regex::new(s)
+    }
+}
+
+/// A single match, with byte offsets into the haystack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'h> {
+    haystack: &'h str,
+    start: usize,
+    end: usize,
+}
+
+impl<'h> Match<'h> {
+    /// Returns the byte offset of the start of the match.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the byte offset of the end of the match.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns `true` if the match is empty.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the length of the match in bytes.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns the byte range of the match.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns the matched text.
+    pub fn as_str(&self) -> &'h str {
+        &self.haystack[self.range()]
+    }
+}
+
+/// The capture groups of a single match.
+#[derive(Debug, Clone)]
+pub struct Captures<'h> {
+    haystack: &'h str,
+    spans: Vec<Option<Range<usize>>>,
+    names: Arc<[Option<String>]>,
+}
+
+impl<'h> Captures<'h> {
+    /// Returns the group with the given number, or `None` if it did not participate.
+    pub fn get(&self, i: usize) -> Option<Match<'h>> {
+        let span = self.spans.get(i)?.as_ref()?;
+        Some(Match {
+            haystack: self.haystack,
+            start: span.start,
+            end: span.end,
+        })
+    }
+
+    /// Returns the group with the given name, or `None` if it did not participate.
+    pub fn name(&self, name: &str) -> Option<Match<'h>> {
+        self.get(self.names.iter().position(|n| n.as_deref() == Some(name))?)
+    }
+
+    /// Returns the overall match (group 0).
+    pub fn get_match(&self) -> Match<'h> {
+        self.get(0).unwrap()
+    }
+
+    /// Returns the number of groups, including group 0.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns `true` if there are no groups, which never happens for a match.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Returns an iterator over all groups, including group 0.
+    pub fn iter(&self) -> impl Iterator<Item = Option<Match<'h>>> + '_ {
+        (0..self.len()).map(|i| self.get(i))
+    }
+
+    /// Expands `$name`/`${name}` references in `replacement` and appends the result to `dst`.
+    /// `$$` is a literal `$`; references to unknown groups expand to nothing.
+    pub fn expand(&self, replacement: &str, dst: &mut String) {
+        let mut rest = replacement;
+        while let Some(i) = rest.find('$') {
+            dst.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                dst.push('$');
+                rest = after;
+                continue;
+            }
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], &braced[end + 1..]),
+                    None => ("", rest),
+                },
+                None => {
+                    let end = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            if name.is_empty() {
+                dst.push('$');
+                continue;
+            }
+            let group = match name.parse::<usize>() {
+                Ok(i) => self.get(i),
+                Err(_) => self.name(name),
+            };
+            if let Some(m) = group {
+                dst.push_str(m.as_str());
+            }
+            rest = after;
+        }
+        dst.push_str(rest);
+    }
+}
+
+impl<'h> Index<usize> for Captures<'h> {
+    type Output = str;
+
+    /// Returns the text of the group; panics if it did not participate.
+    fn index(&self, i: usize) -> &str {
+        self.get(i)
+            .map(|m| m.as_str())
+            .unwrap_or_else(|| panic!("no group at index '{i}'"))
+    }
+}
+
+impl<'h> Index<&str> for Captures<'h> {
+    type Output = str;
+
+    /// Returns the text of the named group; panics if it did not participate.
+    fn index(&self, name: &str) -> &str {
+        self.name(name)
+            .map(|m| m.as_str())
+            .unwrap_or_else(|| panic!("no group named '{name}'"))
+    }
+}
+
+/// A replacement for `Regex::replace` and friends, as in the `regex` crate.
+///
+/// It is implemented for template strings (expanded with `Captures::expand`), for `NoExpand`
+/// literals and for closures `FnMut(&Captures) -> impl AsRef<str>`.
+pub trait Replacer {
+    /// Appends the replacement for the match to `dst`.
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String);
+
+    /// Returns the replacement if it is the same literal for every match.
+    fn no_expansion(&mut self) -> Option<Cow<'_, str>> {
+        None
+    }
+}
+
+/// A literal replacement string, used as-is without expanding `$` references.
+#[derive(Debug, Clone)]
+pub struct NoExpand<'s>(pub &'s str);
+
+impl Replacer for NoExpand<'_> {
+    fn replace_append(&mut self, _: &Captures<'_>, dst: &mut String) {
+        dst.push_str(self.0);
+    }
+
+    fn no_expansion(&mut self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self.0))
+    }
+}
+
+/// Returns the template as a literal if it contains no `$` references.
+fn literal(template: &str) -> Option<Cow<'_, str>> {
+    (!template.contains('$')).then_some(Cow::Borrowed(template))
+}
+
+impl Replacer for &str {
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+
+    fn no_expansion(&mut self) -> Option<Cow<'_, str>> {
+        literal(self)
+    }
+}
+
+impl Replacer for &String {
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+
+    fn no_expansion(&mut self) -> Option<Cow<'_, str>> {
+        literal(self)
+    }
+}
+
+impl Replacer for String {
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+
+    fn no_expansion(&mut self) -> Option<Cow<'_, str>> {
+        literal(self)
+    }
+}
+
+impl Replacer for Cow<'_, str> {
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+
+    fn no_expansion(&mut self) -> Option<Cow<'_, str>> {
+        literal(self)
+    }
+}
+
+impl<F, T> Replacer for F
+where
+    F: FnMut(&Captures<'_>) -> T,
+    T: AsRef<str>,
+{
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        dst.push_str((*self)(caps).as_ref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compat_facade() -> PyResult<()> {
+        let re = Regex::new(r"(?<=€)(?P<amount>\d+)")?;
+        let haystack = "cost: €42, €7";
+        let m = re.find(haystack).unwrap();
+        assert_eq!((m.start(), m.end(), m.as_str()), (9, 11, "42"));
+        let caps = re.captures(haystack).unwrap();
+        assert_eq!(&caps["amount"], "42");
+        assert_eq!(re.find_iter(haystack).count(), 2);
+        assert_eq!(re.replace_all(haystack, "[$amount]"), "cost: €[42], €[7]");
+        assert_eq!(
+            Regex::new(r",\s*")?.split("a, b,c").collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replacer() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = Regex::new(r"(?<=€)(?P<amount>\d+)")?;
+        let haystack = "cost: €42, €7";
+        assert_eq!(re.replace(haystack, NoExpand("$")), "cost: €$, €7");
+        let template = String::from("${amount}0");
+        assert_eq!(re.replace_all(haystack, &template), "cost: €420, €70");
+        assert_eq!(re.replacen(haystack, 1, template), "cost: €420, €7");
+        let doubled = re.replace_all(haystack, |caps: &Captures| {
+            (caps["amount"].parse::<u32>().unwrap() * 2).to_string()
+        });
+        assert_eq!(doubled, "cost: €84, €14");
+        // A haystack without matches is returned borrowed.
+        assert!(matches!(re.replace("none", "x"), Cow::Borrowed("none")));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use crate::flags::Flags;
+use crate::python::PyRegex;
+use crate::template::is_identifier;
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use std::fmt;
+
+/// Composes a pattern from fragments, so that text from configuration or user input is matched
+/// literally instead of being spliced into the pattern by hand.
+///
+/// Literals are escaped, and raw subpatterns, groups and repetitions are each wrapped in a group,
+/// so an alternation inside a fragment never leaks into the rest of the pattern.
+///
+/// ```
+/// use py_regex::PatternBuilder;
+///
+/// let version = PatternBuilder::new().raw(r"\d+").literal(".").raw(r"\d+");
+/// let re = PatternBuilder::new()
+///     .literal("app-1.0 (")
+///     .named_group("version", version)?
+///     .literal(")")
+///     .build()?;
+/// assert!(re.is_match("app-1.0 (2.13)")?);
+/// assert!(!re.is_match("app-1x0 (2.13)")?);
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatternBuilder {
+    pattern: String,
+}
+
+impl PatternBuilder {
+    /// Creates a builder for the empty pattern.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends text that is matched literally. Every ASCII character other than letters, digits
+    /// and `_` is escaped, so the result also holds with `Flags::VERBOSE`.
+    pub fn literal(mut self, text: &str) -> Self {
+        for c in text.chars() {
+            if c.is_ascii() && !c.is_ascii_alphanumeric() && c != '_' {
+                self.pattern.push('\\');
+            }
+            self.pattern.push(c);
+        }
+        self
+    }
+
+    /// Appends a subpattern as it is, inside a non-capturing group.
+    pub fn raw(mut self, subpattern: &str) -> Self {
+        self.pattern.push_str("(?:");
+        self.pattern.push_str(subpattern);
+        self.pattern.push(')');
+        self
+    }
+
+    /// Appends a named capturing group matching `inner`. Returns a `ValueError` if `name` is not
+    /// a valid group name.
+    pub fn named_group(mut self, name: &str, inner: PatternBuilder) -> PyResult<Self> {
+        if !is_identifier(name) {
+            return Err(PyValueError::new_err(format!(
+                "invalid group name `{name}`"
+            )));
+        }
+        self.pattern
+            .push_str(&format!("(?P<{name}>{})", inner.pattern));
+        Ok(self)
+    }
+
+    /// Appends `inner` repeated from `min` to `max` times, or at least `min` times if `max` is
+    /// `None`. Returns a `ValueError` if `max` is less than `min`.
+    pub fn repeat(
+        mut self,
+        inner: PatternBuilder,
+        min: usize,
+        max: Option<usize>,
+    ) -> PyResult<Self> {
+        let quantifier = match max {
+            Some(max) if max < min => {
+                return Err(PyValueError::new_err(format!(
+                    "repetition maximum {max} is less than the minimum {min}"
+                )));
+            }
+            Some(max) if max == min => format!("{{{min}}}"),
+            Some(max) => format!("{{{min},{max}}}"),
+            None => format!("{{{min},}}"),
+        };
+        self.pattern
+            .push_str(&format!("(?:{}){quantifier}", inner.pattern));
+        Ok(self)
+    }
+
+    /// Appends `inner` as an optional part, matched zero or one time.
+    pub fn optional(mut self, inner: PatternBuilder) -> Self {
+        self.pattern.push_str(&format!("(?:{})?", inner.pattern));
+        self
+    }
+
+    /// Returns the pattern composed so far.
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Compiles the composed pattern.
+    pub fn build(&self) -> PyResult<PyRegex> {
+        PyRegex::new(&self.pattern)
+    }
+
+    /// Compiles the composed pattern with the given flags.
+    pub fn build_with_flags(&self, flags: Flags) -> PyResult<PyRegex> {
+        PyRegex::with_flags(&self.pattern, flags)
+    }
+}
+
+impl fmt::Display for PatternBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_builder() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let word = PatternBuilder::new().raw(r"\w+|-");
+        let builder = PatternBuilder::new()
+            .literal("a.b (é)#")
+            .repeat(PatternBuilder::new().literal("x"), 2, Some(3))?
+            .named_group("word", word)?
+            .optional(PatternBuilder::new().literal("!"));
+        assert_eq!(
+            builder.as_str(),
+            r"a\.b\ \(é\)\#(?:x){2,3}(?P<word>(?:\w+|-))(?:\!)?"
+        );
+
+        let re = builder.build_with_flags(Flags::VERBOSE)?;
+        let m = re.search_match("a.b (é)#xxxhi!")?.unwrap();
+        assert_eq!(m.group("word")?.as_deref(), Some("hi"));
+        assert!(!re.is_match("aXb (é)#xxhi")?);
+
+        assert!(
+            PatternBuilder::new()
+                .named_group("1a", PatternBuilder::new())
+                .is_err()
+        );
+        assert!(
+            PatternBuilder::new()
+                .repeat(PatternBuilder::new(), 2, Some(1))
+                .is_err()
+        );
+        assert_eq!(
+            PatternBuilder::new()
+                .repeat(PatternBuilder::new().raw("a"), 1, None)?
+                .to_string(),
+            "(?:(?:a)){1,}"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use crate::lines::LineIndex;
+use crate::match_data::MatchData;
+use crate::offsets::ByteOffsets;
+use crate::python::PyRegex;
+use pyo3::prelude::*;
+
+/// A match together with the lines around it, as returned by `PyRegex::find_with_context`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextMatch {
+    /// The match, with code point spans into the whole text.
+    pub data: MatchData,
+    /// The 1-based number of the line the match starts on.
+    pub line: usize,
+    /// The 1-based column the match starts at, counted in code points.
+    pub column: usize,
+    /// Up to the requested number of lines before the first line of the match.
+    pub before: Vec<String>,
+    /// The lines the match spans, in full.
+    pub lines: Vec<String>,
+    /// Up to the requested number of lines after the last line of the match.
+    pub after: Vec<String>,
+}
+
+impl PyRegex {
+    /// Returns every match with the lines it spans and up to `before` and `after` lines of
+    /// context, like `grep -B before -A after`, along with the line and column where it starts.
+    ///
+    /// Lines are separated by `\n`, and a `\r` before it is not part of the line.
+    ///
+    /// ```
+    /// let re = py_regex::PyRegex::new(r"error: \w+")?;
+    /// let found = re.find_with_context("a\nb\nerror: boom\nc", 1, 1)?;
+    /// assert_eq!((found[0].line, found[0].column), (3, 1));
+    /// assert_eq!(found[0].before, ["b"]);
+    /// assert_eq!(found[0].after, ["c"]);
+    /// # Ok::<(), pyo3::PyErr>(())
+    /// ```
+    pub fn find_with_context(
+        &self,
+        text: &str,
+        before: usize,
+        after: usize,
+    ) -> PyResult<Vec<ContextMatch>> {
+        let offsets = ByteOffsets::new(text);
+        let index = LineIndex::new(text);
+        let lines = |lines: std::ops::Range<usize>| {
+            lines
+                .filter_map(|line| index.line_span(line))
+                .map(|span| text[offsets.get(span.start)..offsets.get(span.end)].to_string())
+                .collect::<Vec<_>>()
+        };
+        self.find_iter(text)?
+            .iter()
+            .map(|m| {
+                let data = m.to_data()?;
+                let span = data.span(0).unwrap_or_default();
+                let (first, column) = index.line_col(span.start);
+                // The line of the last matched character, so a match ending with a line
+                // terminator does not take in the next line.
+                let last = match span.is_empty() {
+                    true => first,
+                    false => index.line_col(span.end - 1).0,
+                };
+                Ok(ContextMatch {
+                    line: first,
+                    column,
+                    before: lines(first.saturating_sub(before).max(1)..first),
+                    lines: lines(first..last + 1),
+                    after: lines(last + 1..last + 1 + after),
+                    data,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_with_context() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let text = "one\r\ntwo é=1\nthree\nfour=2\n";
+        let found = PyRegex::new(r"\w+=\d\n?")?.find_with_context(text, 1, 2)?;
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].line, found[0].column), (2, 5));
+        assert_eq!(found[0].data.as_str(), "é=1\n");
+        assert_eq!(found[0].before, ["one"]);
+        assert_eq!(found[0].lines, ["two é=1"]);
+        assert_eq!(found[0].after, ["three", "four=2"]);
+        assert_eq!((found[1].line, found[1].column), (4, 1));
+        assert!(found[1].after.is_empty());
+
+        let spanning = PyRegex::new(r"one\s+two")?.find_with_context(text, 3, 0)?;
+        assert!(spanning[0].before.is_empty());
+        assert_eq!(spanning[0].lines, ["one", "two é=1"]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//! A common interface over the matching engines, for code that should not depend on which one
+//! runs it.
+
+use crate::flags::Flags;
+use crate::match_data::MatchData;
+
+/// A regular expression engine: compiles patterns and matches them, returning owned
+/// `MatchData` so that results look the same whatever the engine.
+///
+/// `PythonEngine` runs the Python `regex` module (or `re` with `set_re_fallback`) and
+/// `FancyEngine` runs `fancy-regex`. Other engines, such as a remote matching service, can
+/// implement the trait and be used by the same generic code.
+///
+/// ```
+/// use py_regex::{Engine, Flags};
+///
+/// fn mask_years<E: Engine>(engine: &E, text: &str) -> Result<String, E::Error> {
+///     let re = engine.compile(r"\b\d{4}\b", Flags::NONE)?;
+///     engine.sub(&re, "####", text)
+/// }
+///
+/// #[cfg(feature = "python")]
+/// assert_eq!(mask_years(&py_regex::PythonEngine, "since 1999")?, "since ####");
+/// #[cfg(feature = "fallback-fancy-regex")]
+/// assert_eq!(mask_years(&py_regex::FancyEngine, "since 1999")?, "since ####");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait Engine {
+    /// A compiled pattern.
+    type Pattern;
+    /// The error returned by every operation.
+    type Error: std::error::Error;
+
+    /// Compiles the pattern with the given flags.
+    fn compile(&self, pattern: &str, flags: Flags) -> Result<Self::Pattern, Self::Error>;
+
+    /// Returns the first match in the text.
+    fn search(&self, pattern: &Self::Pattern, text: &str)
+    -> Result<Option<MatchData>, Self::Error>;
+
+    /// Returns all non-overlapping matches in the text.
+    fn find_iter(&self, pattern: &Self::Pattern, text: &str)
+    -> Result<Vec<MatchData>, Self::Error>;
+
+    /// Replaces all matches, expanding `\1` and `\g<name>` references in the replacement.
+    fn sub(
+        &self,
+        pattern: &Self::Pattern,
+        replacement: &str,
+        text: &str,
+    ) -> Result<String, Self::Error>;
+}
+
+/// The Python `regex` module, or `re` if the fallback is active.
+#[cfg(feature = "python")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PythonEngine;
+
+#[cfg(feature = "python")]
+impl Engine for PythonEngine {
+    type Pattern = crate::python::PyRegex;
+    type Error = pyo3::PyErr;
+
+    fn compile(&self, pattern: &str, flags: Flags) -> pyo3::PyResult<Self::Pattern> {
+        crate::python::PyRegex::with_flags(pattern, flags)
+    }
+
+    fn search(&self, pattern: &Self::Pattern, text: &str) -> pyo3::PyResult<Option<MatchData>> {
+        pattern.search_match(text)?.map(|m| m.to_data()).transpose()
+    }
+
+    fn find_iter(&self, pattern: &Self::Pattern, text: &str) -> pyo3::PyResult<Vec<MatchData>> {
+        pattern
+            .find_iter(text)?
+            .iter()
+            .map(|m| m.to_data())
+            .collect()
+    }
+
+    fn sub(
+        &self,
+        pattern: &Self::Pattern,
+        replacement: &str,
+        text: &str,
+    ) -> pyo3::PyResult<String> {
+        pattern.replace(text, replacement)
+    }
+}
+
+/// The pure-Rust `fancy-regex` engine.
+#[cfg(feature = "fallback-fancy-regex")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FancyEngine;
+
+#[cfg(feature = "fallback-fancy-regex")]
+impl Engine for FancyEngine {
+    type Pattern = crate::fancy::PyRegex;
+    type Error = crate::fancy::Error;
+
+    fn compile(&self, pattern: &str, flags: Flags) -> crate::fancy::PyResult<Self::Pattern> {
+        crate::fancy::PyRegex::with_flags(pattern, flags)
+    }
+
+    fn search(
+        &self,
+        pattern: &Self::Pattern,
+        text: &str,
+    ) -> crate::fancy::PyResult<Option<MatchData>> {
+        pattern.search_match(text)?.map(|m| m.to_data()).transpose()
+    }
+
+    fn find_iter(
+        &self,
+        pattern: &Self::Pattern,
+        text: &str,
+    ) -> crate::fancy::PyResult<Vec<MatchData>> {
+        pattern
+            .find_iter(text)?
+            .iter()
+            .map(|m| m.to_data())
+            .collect()
+    }
+
+    fn sub(
+        &self,
+        pattern: &Self::Pattern,
+        replacement: &str,
+        text: &str,
+    ) -> crate::fancy::PyResult<String> {
+        pattern.replace(text, replacement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Exercises an engine through the trait only.
+    #[allow(dead_code)]
+    fn check<E: Engine>(engine: &E) -> Result<(), E::Error> {
+        let re = engine.compile(r"(?P<key>\w+)=(\d+)", Flags::NONE)?;
+        let m = engine.search(&re, "x a=1 b=22")?.expect("a match");
+        assert_eq!((&m[0], &m["key"], m.span(2)), ("a=1", "a", Some(4..5)));
+        let all = engine.find_iter(&re, "a=1 b=22")?;
+        assert_eq!(all.iter().map(|m| &m[2]).collect::<Vec<_>>(), ["1", "22"]);
+        assert_eq!(engine.sub(&re, r"\2=\g<key>", "a=1 b=22")?, "1=a 22=b");
+        Ok(())
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn test_python_engine() -> pyo3::PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+        check(&PythonEngine)
+    }
+
+    #[cfg(feature = "fallback-fancy-regex")]
+    #[test]
+    fn test_fancy_engine() -> crate::fancy::PyResult<()> {
+        check(&FancyEngine)
+    }
+}
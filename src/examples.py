@@ -0,0 +1,136 @@
+"""Generates strings matching a pattern, for `PyRegex::examples`.
+
+Candidates come from `exrex` when it is installed and otherwise from a walk over the pattern's
+`re` parse tree. Either way, only candidates the compiled pattern fully matches are returned.
+"""
+
+import random
+import sys
+
+try:
+    import exrex
+except ImportError:
+    exrex = None
+
+# Extra repetitions generated for unbounded quantifiers.
+MAX_EXTRA_REPEAT = 3
+# Flags with the same value in `re` and `regex`: IGNORECASE, MULTILINE, DOTALL and VERBOSE.
+PARSE_FLAGS = 0x2 | 0x8 | 0x10 | 0x40
+PRINTABLE = [chr(c) for c in range(32, 127)]
+
+# The parser of the `re` module is private: `re._parser` since Python 3.11 and `sre_parse` before.
+# The walk below is written against its layout in Python 3.8 to 3.13; if the module is missing or
+# its constants changed, only `exrex` can generate examples and `examples` says so.
+try:
+    try:
+        from re import _constants as sre_constants, _parser as sre_parse
+    except ImportError:  # Python < 3.11
+        import sre_constants
+        import sre_parse
+    CATEGORIES = {
+        sre_constants.CATEGORY_DIGIT: "0123456789",
+        sre_constants.CATEGORY_NOT_DIGIT: "abcXYZ_ -",
+        sre_constants.CATEGORY_SPACE: " \t\n",
+        sre_constants.CATEGORY_NOT_SPACE: "abcXYZ019_-",
+        sre_constants.CATEGORY_WORD: "abcxyzABCXYZ019_",
+        sre_constants.CATEGORY_NOT_WORD: " -.,;:!?",
+    }
+    REPEATS = {
+        sre_constants.MAX_REPEAT,
+        sre_constants.MIN_REPEAT,
+        getattr(sre_constants, "POSSESSIVE_REPEAT", None),
+    }
+    PARSER_ERROR = None
+except (ImportError, AttributeError) as err:
+    sre_constants = sre_parse = None
+    CATEGORIES, REPEATS = {}, set()
+    PARSER_ERROR = err
+
+
+class Generator:
+    def __init__(self, rng):
+        self.rng = rng
+        self.groups = {}
+
+    def sequence(self, items):
+        return "".join(self.item(op, av) for op, av in items)
+
+    def item(self, op, av):
+        c = sre_constants
+        if op == c.LITERAL:
+            return chr(av)
+        if op == c.NOT_LITERAL:
+            return self.rng.choice([ch for ch in PRINTABLE if ord(ch) != av])
+        if op == c.ANY:
+            return self.rng.choice(PRINTABLE)
+        if op == c.IN:
+            return self.charset(av)
+        if op == c.BRANCH:
+            return self.sequence(self.rng.choice(av[1]))
+        if op == c.SUBPATTERN:
+            text = self.sequence(av[-1])
+            if av[0] is not None:
+                self.groups[av[0]] = text
+            return text
+        if op in REPEATS:
+            low, high, body = av
+            count = self.rng.randint(low, min(high, low + MAX_EXTRA_REPEAT))
+            return "".join(self.sequence(body) for _ in range(count))
+        if op == c.GROUPREF:
+            return self.groups.get(av, "")
+        if op == c.GROUPREF_EXISTS:
+            group, yes, no = av
+            return self.sequence(yes if group in self.groups else no or [])
+        if op == getattr(c, "ATOMIC_GROUP", None):
+            return self.sequence(av)
+        # Anchors and lookarounds produce no text; candidates that violate them are rejected.
+        return ""
+
+    def charset(self, items):
+        c = sre_constants
+        allowed = set()
+        for op, av in items:
+            if op == c.LITERAL:
+                allowed.add(chr(av))
+            elif op == c.RANGE:
+                allowed.update(chr(code) for code in range(av[0], min(av[1], av[0] + 255) + 1))
+            elif op == c.CATEGORY:
+                allowed.update(CATEGORIES.get(av, ""))
+        if items and items[0][0] == c.NEGATE:
+            allowed = {ch for ch in PRINTABLE if ch not in allowed}
+        return self.rng.choice(sorted(allowed)) if allowed else ""
+
+
+def parse(compiled):
+    if sre_parse is None:
+        raise RuntimeError(
+            "generating examples without exrex needs the parser of the `re` module, which "
+            f"Python {sys.version.split()[0]} does not provide as expected ({PARSER_ERROR!r}); "
+            "install exrex"
+        )
+    return sre_parse.parse(compiled.pattern, compiled.flags & PARSE_FLAGS)
+
+
+def examples(compiled, count, attempts_per_example):
+    # A fixed seed keeps the results repeatable.
+    rng = random.Random(0)
+    tree = None
+    use_exrex = exrex is not None
+    results, seen = [], set()
+    for _ in range(count * attempts_per_example):
+        if len(results) >= count:
+            break
+        if use_exrex:
+            try:
+                candidate = exrex.getone(compiled.pattern)
+            except Exception:
+                use_exrex = False
+                continue
+        else:
+            if tree is None:
+                tree = parse(compiled)
+            candidate = Generator(rng).sequence(tree)
+        if candidate not in seen and compiled.fullmatch(candidate):
+            seen.add(candidate)
+            results.append(candidate)
+    return results
@@ -0,0 +1,69 @@
+use crate::helpers::EmbeddedModule;
+use crate::python::PyRegex;
+use pyo3::prelude::*;
+
+/// The Python side of `PyRegex::examples`.
+pub(crate) static GENERATOR: EmbeddedModule = EmbeddedModule::new(
+    pyo3::ffi::c_str!(include_str!("examples.py")),
+    c"py_regex/examples.py",
+    c"py_regex_examples",
+);
+
+/// Number of attempts per requested example before giving up.
+const ATTEMPTS_PER_EXAMPLE: usize = 50;
+
+impl PyRegex {
+    /// Returns up to `n` distinct strings that the whole pattern matches, e.g. to fuzz the
+    /// parsers fed by a rule or to show what it accepts.
+    ///
+    /// Candidates are generated with `exrex` if it is installed and otherwise from the parse tree
+    /// of the pattern, then checked against the pattern, so fewer than `n` are returned for
+    /// patterns with few matching strings or with lookarounds that most candidates violate.
+    /// Without `exrex`, syntax that only the `regex` module supports, such as `\p{...}` or fuzzy
+    /// constraints, fails with an error, and so does every pattern on a Python version whose
+    /// private `re` parser is missing or laid out differently than in Python 3.8 to 3.13. The
+    /// generator is seeded, so the results are repeatable unless `exrex` is used.
+    pub fn examples(&self, n: usize) -> PyResult<Vec<String>> {
+        Python::with_gil(|py| {
+            GENERATOR
+                .get(py)?
+                .call_method1("examples", (self.as_py(), n, ATTEMPTS_PER_EXAMPLE))?
+                .extract()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_examples() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(?P<id>[A-F]\d{2,})-(?:x|yz)+\b(?P=id)?")?;
+        let examples = re.examples(5)?;
+        assert_eq!(examples.len(), 5);
+        for example in &examples {
+            assert!(re.is_match(example)?, "{example}");
+        }
+        assert_eq!(re.examples(5)?, examples);
+        assert_eq!(PyRegex::new("a|b")?.examples(5)?.len(), 2);
+
+        // Without exrex and the `re` parser, the error says what is missing.
+        let err = Python::with_gil(|py| {
+            let module = GENERATOR.get(py)?;
+            let saved = (module.getattr("exrex")?, module.getattr("sre_parse")?);
+            module.setattr("exrex", py.None())?;
+            module.setattr("sre_parse", py.None())?;
+            let result = re.examples(1);
+            module.setattr("exrex", saved.0)?;
+            module.setattr("sre_parse", saved.1)?;
+            PyResult::Ok(result.unwrap_err())
+        })?;
+        assert!(err.to_string().contains("install exrex"), "{err}");
+        assert_eq!(re.examples(5)?, examples);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,655 @@
+//! A pure-Rust backend built on `fancy-regex`, for building and testing without a Python toolchain.
+//!
+//! It implements the subset of the `PyRegex` API that `fancy-regex` can express. Positions are
+//! reported in characters and replacement templates use Python syntax (`\1`, `\g<name>`), as with
+//! the Python backend. Python-only syntax such as fuzzy matching fails to compile.
+
+use crate::flags::Flags;
+use crate::group::Group;
+use crate::match_data::MatchData;
+use crate::offsets::ByteOffsets;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+/// An error from the `fancy-regex` backend.
+#[derive(Debug)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<fancy_regex::Error> for Error {
+    fn from(err: fancy_regex::Error) -> Self {
+        Error(err.to_string())
+    }
+}
+
+/// Result type of the `fancy-regex` backend, named after its Python counterpart.
+pub type PyResult<T> = Result<T, Error>;
+
+/// Characters escaped by `escape` with `special_only`, as in the Python `regex` module.
+const SPECIAL_CHARS: &str = "()[]{}?*+|^$\\.-#&~";
+
+/// A compiled regular expression backed by `fancy-regex`.
+#[derive(Debug, Clone)]
+pub struct PyRegex {
+    compiled: fancy_regex::Regex,
+}
+
+impl PyRegex {
+    /// Compiles the pattern.
+    pub fn new(pattern: &str) -> PyResult<Self> {
+        Self::with_flags(pattern, Flags::NONE)
+    }
+
+    /// Compiles the pattern with the given flags.
+    /// Only `IGNORECASE`, `MULTILINE`, `DOTALL`, `VERBOSE` and `UNICODE` are supported.
+    pub fn with_flags(pattern: &str, flags: Flags) -> PyResult<Self> {
+        let mut inline = String::new();
+        let mut supported = Flags::UNICODE;
+        for (flag, c) in [
+            (Flags::IGNORECASE, 'i'),
+            (Flags::MULTILINE, 'm'),
+            (Flags::DOTALL, 's'),
+            (Flags::VERBOSE, 'x'),
+        ] {
+            supported |= flag;
+            if flags.contains(flag) {
+                inline.push(c);
+            }
+        }
+        let unsupported = flags.bits() & !supported.bits();
+        if unsupported != 0 {
+            return Err(Error(format!(
+                "flags {unsupported:#x} are not supported by the fancy-regex backend"
+            )));
+        }
+        let compiled = if inline.is_empty() {
+            fancy_regex::Regex::new(pattern)?
+        } else {
+            fancy_regex::Regex::new(&format!("(?{inline}){pattern}"))?
+        };
+        Ok(PyRegex { compiled })
+    }
+
+    /// Performs a search for the first match and returns a `PyRegexMatch` object.
+    pub fn search_match(&self, text: impl AsRef<str>) -> PyResult<Option<PyRegexMatch>> {
+        let text = text.as_ref();
+        Ok(self
+            .compiled
+            .captures(text)?
+            .map(|caps| PyRegexMatch::new(&self.compiled, &caps, &ByteOffsets::new(text))))
+    }
+
+    /// Returns all non-overlapping matches.
+    pub fn find_iter(&self, text: impl AsRef<str>) -> PyResult<Vec<PyRegexMatch>> {
+        let text = text.as_ref();
+        let offsets = ByteOffsets::new(text);
+        self.compiled
+            .captures_iter(text)
+            .map(|caps| Ok(PyRegexMatch::new(&self.compiled, &caps?, &offsets)))
+            .collect()
+    }
+
+    /// Returns the match at index `n` (0-based) of `find_iter`, without searching past it.
+    pub fn find_nth(&self, text: impl AsRef<str>, n: usize) -> PyResult<Option<PyRegexMatch>> {
+        let text = text.as_ref();
+        self.compiled
+            .captures_iter(text)
+            .nth(n)
+            .map(|caps| {
+                Ok(PyRegexMatch::new(
+                    &self.compiled,
+                    &caps?,
+                    &ByteOffsets::new(text),
+                ))
+            })
+            .transpose()
+    }
+
+    /// Returns the last match of `find_iter`.
+    pub fn find_last(&self, text: impl AsRef<str>) -> PyResult<Option<PyRegexMatch>> {
+        let text = text.as_ref();
+        let mut last = None;
+        for caps in self.compiled.captures_iter(text) {
+            last = Some(caps?);
+        }
+        Ok(last.map(|caps| PyRegexMatch::new(&self.compiled, &caps, &ByteOffsets::new(text))))
+    }
+
+    /// Returns the byte offset and text of every match, like `str::match_indices`.
+    pub fn match_indices(
+        &self,
+        text: impl AsRef<str>,
+    ) -> PyResult<impl Iterator<Item = (usize, String)>> {
+        let text = text.as_ref();
+        let found = self
+            .compiled
+            .find_iter(text)
+            .map(|m| {
+                let m = m?;
+                Ok((m.start(), m.as_str().to_string()))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(found.into_iter())
+    }
+
+    /// Returns `true` if the pattern matches anywhere in the text.
+    pub fn is_match(&self, text: impl AsRef<str>) -> PyResult<bool> {
+        let text = text.as_ref();
+        Ok(self.compiled.is_match(text)?)
+    }
+
+    /// Returns all matches like Python's `findall`: the whole match without groups, the first
+    /// group with exactly one group, and an error with more than one.
+    pub fn find_all(&self, text: impl AsRef<str>) -> PyResult<Vec<String>> {
+        let text = text.as_ref();
+        let group = match self.compiled.captures_len() {
+            1 => 0,
+            2 => 1,
+            _ => {
+                return Err(Error(
+                    "find_all does not support patterns with several groups".to_string(),
+                ));
+            }
+        };
+        self.compiled
+            .captures_iter(text)
+            .map(|caps| {
+                Ok(caps?
+                    .get(group)
+                    .map_or_else(String::new, |m| m.as_str().to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns the groups of every match, with `None` for groups that did not participate, or the
+    /// whole match for patterns without groups.
+    pub fn find_all_groups(&self, text: impl AsRef<str>) -> PyResult<Vec<Vec<Option<String>>>> {
+        let text = text.as_ref();
+        let skip = usize::from(self.compiled.captures_len() > 1);
+        self.compiled
+            .captures_iter(text)
+            .map(|caps| {
+                Ok(caps?
+                    .iter()
+                    .skip(skip)
+                    .map(|group| group.map(|m| m.as_str().to_string()))
+                    .collect())
+            })
+            .collect()
+    }
+
+    /// Replaces all matches, expanding `\1` and `\g<name>` references in the replacement.
+    pub fn replace(&self, text: impl AsRef<str>, replacement: impl AsRef<str>) -> PyResult<String> {
+        let (text, replacement) = (text.as_ref(), replacement.as_ref());
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for caps in self.compiled.captures_iter(text) {
+            let caps = caps?;
+            let m = caps.get(0).unwrap();
+            result.push_str(&text[last..m.start()]);
+            self.expand(&caps, replacement, &mut result)?;
+            last = m.end();
+        }
+        result.push_str(&text[last..]);
+        Ok(result)
+    }
+
+    /// Keeps only the matched parts of the text, joined by `separator`.
+    pub fn extract_joined(&self, text: impl AsRef<str>, separator: &str) -> PyResult<String> {
+        let text = text.as_ref();
+        let parts = self
+            .compiled
+            .find_iter(text)
+            .map(|m| Ok(m?.as_str()))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(parts.join(separator))
+    }
+
+    /// Deletes every match from the text.
+    pub fn remove(&self, text: impl AsRef<str>) -> PyResult<String> {
+        let text = text.as_ref();
+        Ok(self.remove_count(text)?.0)
+    }
+
+    /// Same as `remove`, also returning the number of deleted matches.
+    pub fn remove_count(&self, text: impl AsRef<str>) -> PyResult<(String, usize)> {
+        let text = text.as_ref();
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        let mut count = 0;
+        for m in self.compiled.find_iter(text) {
+            let m = m?;
+            result.push_str(&text[last..m.start()]);
+            last = m.end();
+            count += 1;
+        }
+        result.push_str(&text[last..]);
+        Ok((result, count))
+    }
+
+    /// Splits the text by the matches, including captured groups like Python's `split`.
+    pub fn split(&self, text: impl AsRef<str>) -> PyResult<Vec<String>> {
+        let text = text.as_ref();
+        let mut parts = Vec::new();
+        let mut last = 0;
+        for caps in self.compiled.captures_iter(text) {
+            let caps = caps?;
+            let m = caps.get(0).unwrap();
+            parts.push(text[last..m.start()].to_string());
+            for group in caps.iter().skip(1) {
+                let group = group.ok_or_else(|| {
+                    Error("split produced a group that did not participate".to_string())
+                })?;
+                parts.push(group.as_str().to_string());
+            }
+            last = m.end();
+        }
+        parts.push(text[last..].to_string());
+        Ok(parts)
+    }
+
+    /// Splits the text by the matches, without captured groups.
+    pub fn split_discard(&self, text: impl AsRef<str>) -> PyResult<Vec<String>> {
+        let text = text.as_ref();
+        self.split_delimited(text, false)
+    }
+
+    /// Splits the text by the matches, with each whole match between the pieces it separates.
+    pub fn split_keep(&self, text: impl AsRef<str>) -> PyResult<Vec<String>> {
+        let text = text.as_ref();
+        self.split_delimited(text, true)
+    }
+
+    fn split_delimited(&self, text: &str, keep: bool) -> PyResult<Vec<String>> {
+        let mut parts = Vec::new();
+        let mut last = 0;
+        for m in self.compiled.find_iter(text) {
+            let m = m?;
+            parts.push(text[last..m.start()].to_string());
+            if keep {
+                parts.push(m.as_str().to_string());
+            }
+            last = m.end();
+        }
+        parts.push(text[last..].to_string());
+        Ok(parts)
+    }
+
+    /// Escapes a string like the Python `regex.escape`.
+    pub fn escape(str: &str, special_only: bool, literal_spaces: bool) -> PyResult<String> {
+        let mut escaped = String::with_capacity(str.len());
+        for c in str.chars() {
+            if c == ' ' {
+                escaped.push_str(if literal_spaces { " " } else { "\\ " });
+            } else if c == '\0' {
+                escaped.push_str("\\x00");
+            } else {
+                let special = if special_only {
+                    SPECIAL_CHARS.contains(c)
+                } else {
+                    !c.is_ascii_alphanumeric()
+                };
+                if special {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+        }
+        Ok(escaped)
+    }
+
+    /// Appends the replacement template expanded against `caps` to `dst`.
+    fn expand(
+        &self,
+        caps: &fancy_regex::Captures,
+        template: &str,
+        dst: &mut String,
+    ) -> PyResult<()> {
+        let group = |index: usize| -> PyResult<&str> {
+            if index >= caps.len() {
+                return Err(Error(format!("invalid group reference {index}")));
+            }
+            Ok(caps.get(index).map_or("", |m| m.as_str()))
+        };
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                dst.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('g') if chars.peek() == Some(&'<') => {
+                    chars.next();
+                    let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+                    let index = match name.parse::<usize>() {
+                        Ok(index) => index,
+                        Err(_) => self
+                            .compiled
+                            .capture_names()
+                            .position(|n| n == Some(name.as_str()))
+                            .ok_or_else(|| Error(format!("unknown group name '{name}'")))?,
+                    };
+                    dst.push_str(group(index)?);
+                }
+                Some(d) if d.is_ascii_digit() => {
+                    let mut index = d.to_digit(10).unwrap() as usize;
+                    if let Some(next) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        chars.next();
+                        index = index * 10 + next as usize;
+                    }
+                    dst.push_str(group(index)?);
+                }
+                Some('n') => dst.push('\n'),
+                Some('t') => dst.push('\t'),
+                Some('r') => dst.push('\r'),
+                Some('\\') => dst.push('\\'),
+                Some(other) => {
+                    dst.push('\\');
+                    dst.push(other);
+                }
+                None => dst.push('\\'),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A match produced by the `fancy-regex` backend.
+/// Group texts and positions are copied out of the haystack, so the match owns its data.
+#[derive(Debug, Clone)]
+pub struct PyRegexMatch {
+    groups: Vec<Option<(isize, isize, String)>>,
+    names: HashMap<String, usize>,
+}
+
+impl PyRegexMatch {
+    /// Copies the groups out of `caps`, converting their byte offsets with the `offsets` of the
+    /// haystack, which are shared by all matches in it.
+    fn new(re: &fancy_regex::Regex, caps: &fancy_regex::Captures, offsets: &ByteOffsets) -> Self {
+        let char_offset = |byte: usize| offsets.char_index(byte) as isize;
+        PyRegexMatch {
+            groups: caps
+                .iter()
+                .map(|m| {
+                    m.map(|m| {
+                        (
+                            char_offset(m.start()),
+                            char_offset(m.end()),
+                            m.as_str().to_string(),
+                        )
+                    })
+                })
+                .collect(),
+            names: re
+                .capture_names()
+                .enumerate()
+                .filter_map(|(index, name)| Some((name?.to_string(), index)))
+                .collect(),
+        }
+    }
+
+    fn get(&self, group: Group) -> PyResult<&Option<(isize, isize, String)>> {
+        let index = match group {
+            Group::Index(index) => Some(index),
+            Group::Name(name) => self.names.get(name).copied(),
+        };
+        index
+            .and_then(|index| self.groups.get(index))
+            .ok_or_else(|| Error(format!("no such group: {group}")))
+    }
+
+    /// Returns the match for the specified group, by number or by name.
+    pub fn group<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<Option<String>> {
+        Ok(self
+            .get(group.into())?
+            .as_ref()
+            .map(|(_, _, text)| text.clone()))
+    }
+
+    /// Returns all captured groups, starting from 1.
+    pub fn groups(&self) -> PyResult<Vec<Option<String>>> {
+        Ok(self.groups[1..]
+            .iter()
+            .map(|g| g.as_ref().map(|(_, _, text)| text.clone()))
+            .collect())
+    }
+
+    /// Copies the text and span of every group into a `MatchData`.
+    pub fn to_data(&self) -> PyResult<MatchData> {
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| {
+                group
+                    .as_ref()
+                    .map(|(start, end, text)| (text.clone(), *start as usize..*end as usize))
+            })
+            .collect();
+        Ok(MatchData::new(groups, self.names.clone()))
+    }
+
+    /// Returns an iterator over groups 1 to N, with `None` for groups that did not participate.
+    pub fn iter_groups(&self) -> PyResult<std::vec::IntoIter<Option<String>>> {
+        Ok(self.groups()?.into_iter())
+    }
+
+    /// Returns the named groups as a `HashMap`.
+    pub fn groupdict(&self) -> PyResult<HashMap<String, Option<String>>> {
+        self.names
+            .iter()
+            .map(|(name, &index)| Ok((name.clone(), self.group(index)?)))
+            .collect()
+    }
+
+    /// Returns the code point span of the group, or `None` if it did not participate.
+    pub fn span<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<Option<Range<usize>>> {
+        Ok(self
+            .get(group.into())?
+            .as_ref()
+            .map(|(start, end, _)| *start as usize..*end as usize))
+    }
+
+    /// Returns the code point span of the named group, or `None` if it did not participate.
+    #[deprecated(note = "use `span`, which accepts group names")]
+    pub fn span_name(&self, name: &str) -> PyResult<Option<Range<usize>>> {
+        self.span(name)
+    }
+
+    /// Returns the start position of the group, or `-1` if it did not participate.
+    #[deprecated(note = "use `span`, which returns `None` instead of -1")]
+    pub fn start<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<isize> {
+        Ok(self
+            .get(group.into())?
+            .as_ref()
+            .map_or(-1, |(start, _, _)| *start))
+    }
+
+    /// Returns the end position of the group, or `-1` if it did not participate.
+    #[deprecated(note = "use `span`, which returns `None` instead of -1")]
+    pub fn end<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<isize> {
+        Ok(self
+            .get(group.into())?
+            .as_ref()
+            .map_or(-1, |(_, end, _)| *end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fancy_match_methods() -> PyResult<()> {
+        let re = PyRegex::new(r"(?P<word>\w+)-(\d+)")?;
+        let m = re.search_match("héllo Tést-123")?.expect("no match");
+        assert_eq!(m.group(0)?, Some("Tést-123".to_string()));
+        assert_eq!(
+            m.groupdict()?.get("word").cloned(),
+            Some(Some("Tést".to_string()))
+        );
+        assert_eq!(re.replace("a-1 b-2", r"\2:\g<word>")?, "1:a 2:b");
+
+        let spans = PyRegex::new(r"\w")?
+            .find_iter("aé😀b")?
+            .iter()
+            .map(|m| m.span(0))
+            .collect::<PyResult<Vec<_>>>()?;
+        assert_eq!(spans, [Some(0..1), Some(1..2), Some(3..4)]);
+        let m = PyRegex::new(r"(x)|é(y)?")?.find_last("xé")?.unwrap();
+        assert_eq!((m.span(0)?, m.span(2)?), (Some(1..2), None));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_find_all_groups() -> PyResult<()> {
+        let re = PyRegex::new(r"(\w+)=(\d+)?(;)?")?;
+        assert_eq!(
+            re.find_all_groups("a=1; b=")?,
+            [
+                vec![Some("a".into()), Some("1".into()), Some(";".into())],
+                vec![Some("b".into()), None, None],
+            ]
+        );
+        assert_eq!(
+            PyRegex::new(r"\d")?.find_all_groups("1a2")?,
+            [[Some("1".to_string())], [Some("2".to_string())]]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_split_keep_discard() -> PyResult<()> {
+        let re = PyRegex::new(r"\s*([,;])|(!)")?;
+        assert_eq!(re.split_discard("é , b;c")?, ["é", " b", "c"]);
+        assert_eq!(
+            re.split_keep("é , b;c!")?,
+            ["é", " ,", " b", ";", "c", "!", ""]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_span() -> PyResult<()> {
+        let m = PyRegex::new(r"é(\d+)(x)?")?
+            .search_match("café42")?
+            .unwrap();
+        assert_eq!(m.span(0)?, Some(3..6));
+        assert_eq!(m.span(1)?, Some(4..6));
+        assert_eq!(m.span(2)?, None);
+        assert!(m.span(3).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_span_by_name() -> PyResult<()> {
+        let m = PyRegex::new(r"(?P<word>\w+)-(?P<number>\d+)?")?
+            .search_match("Tést-")?
+            .unwrap();
+        assert_eq!(m.span("word")?, Some(0..4));
+        assert_eq!(m.span("number")?, None);
+        assert!(m.span("missing").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_group_by_number_or_name() -> PyResult<()> {
+        let m = PyRegex::new(r"(?P<word>\w+)-(\d+)")?
+            .search_match("Tést-123")?
+            .unwrap();
+        let (word, number): (u16, u32) = (1, 2);
+        assert_eq!(m.group(word)?.as_deref(), Some("Tést"));
+        assert_eq!(m.span(number)?, Some(5..8));
+        assert!(m.group(-1).is_err());
+        assert_eq!(m.group("word")?.as_deref(), Some("Tést"));
+        assert!(m.group("missing").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_iter_groups() -> PyResult<()> {
+        let m = PyRegex::new(r"(a)|(b)")?.search_match("b")?.unwrap();
+        assert_eq!(
+            m.iter_groups()?.collect::<Vec<_>>(),
+            [None, Some("b".to_string())]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_find_nth_last() -> PyResult<()> {
+        let re = PyRegex::new(r"\d+")?;
+        let nth = re.find_nth("a1 é22 333", 1)?.unwrap();
+        assert_eq!(nth.group(0)?.as_deref(), Some("22"));
+        assert_eq!(nth.span(0)?, Some(4..6));
+        assert!(re.find_nth("a1 é22 333", 3)?.is_none());
+        let last = re.find_last("a1 é22 333")?.unwrap();
+        assert_eq!(last.span(0)?, Some(7..10));
+        assert!(re.find_last("abc")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_match_indices() -> PyResult<()> {
+        let re = PyRegex::new(r"\d+")?;
+        let found = re.match_indices("é1 22")?.collect::<Vec<_>>();
+        assert_eq!(found, [(2, "1".to_string()), (4, "22".to_string())]);
+        assert_eq!(re.match_indices("abc")?.count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_remove() -> PyResult<()> {
+        let re = PyRegex::new(r"\s*\([^)]*\)")?;
+        assert_eq!(re.remove("Rust (lang) é (x)!")?, "Rust é!");
+        assert_eq!(re.remove_count("a (1) (2)")?, ("a".to_string(), 2));
+        assert_eq!(re.remove_count("none")?, ("none".to_string(), 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fancy_extract_joined() -> PyResult<()> {
+        let re = PyRegex::new(r"\s*\([^)]*\)")?;
+        assert_eq!(
+            re.extract_joined("Rust (lang) é (x)!", "|")?,
+            " (lang)| (x)"
+        );
+        assert_eq!(re.extract_joined("none", "|")?, "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape() -> PyResult<()> {
+        assert_eq!(PyRegex::escape("[]", false, false)?, "\\[\\]");
+        assert_eq!(PyRegex::escape("a\0_é", false, false)?, "a\\x00\\_\\é");
+        assert_eq!(PyRegex::escape("a\0_é", true, false)?, "a\\x00_é");
+
+        for text in ["a.b*c", "x\0y", "snake_case", "café au lait", "(1+1)=2?"] {
+            for (special_only, literal_spaces) in [(false, false), (true, false), (false, true)] {
+                let escaped = PyRegex::escape(text, special_only, literal_spaces)?;
+                let re = PyRegex::new(&format!("^{escaped}$"))?;
+                assert!(re.is_match(text)?, "{text:?} escaped as {escaped:?}");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+//! A C ABI over the wrapper, enabled by the `ffi` feature. Build it as a shared library with
+//! `cargo rustc --release --features ffi --crate-type cdylib`; the declarations are in
+//! `include/py_regex.h`.
+//!
+//! Ownership rules:
+//!
+//! - `py_regex_compile` and `py_regex_search` hand out objects owned by the caller, which must
+//!   release them with `py_regex_free` and `py_regex_match_free` respectively, exactly once.
+//! - Strings returned by `py_regex_match_group` and `py_regex_last_error` are owned by the
+//!   library. Group texts stay valid until the match is freed; the error message stays valid until
+//!   the next call on the same thread.
+//! - Input strings are borrowed for the duration of the call only.
+//!
+//! Functions return `PY_REGEX_OK` (0) or a positive status on success and a negative error code
+//! on failure, in which case `py_regex_last_error` describes the error. Panics are caught and
+//! reported as `PY_REGEX_ERROR_PANIC` instead of unwinding into C.
+
+use crate::flags::Flags;
+use crate::match_data::MatchData;
+use crate::offsets::char_to_byte_offset;
+use crate::python::PyRegex;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// The call succeeded.
+pub const PY_REGEX_OK: c_int = 0;
+/// The search found no match.
+pub const PY_REGEX_NO_MATCH: c_int = 1;
+/// The group exists but did not participate in the match.
+pub const PY_REGEX_NO_GROUP: c_int = 2;
+/// A required pointer was null, or a group number or name does not exist.
+pub const PY_REGEX_ERROR_ARGUMENT: c_int = -1;
+/// An input string is not valid UTF-8.
+pub const PY_REGEX_ERROR_UTF8: c_int = -2;
+/// Python raised an exception, e.g. for an invalid pattern or a timeout, or returned a span that
+/// does not fit the text.
+pub const PY_REGEX_ERROR_PYTHON: c_int = -3;
+/// The library panicked.
+pub const PY_REGEX_ERROR_PANIC: c_int = -4;
+
+/// A compiled pattern, created by `py_regex_compile`.
+pub struct PyRegexHandle {
+    re: PyRegex,
+}
+
+/// The NUL-terminated text and byte span of a group, or `None` if it did not participate.
+type ByteGroup = Option<(CString, Range<usize>)>;
+
+/// A match, created by `py_regex_search`: the NUL-terminated text and byte span of each group.
+pub struct PyRegexMatchHandle {
+    groups: Vec<ByteGroup>,
+    names: Vec<(String, usize)>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records the message returned by `py_regex_last_error` on this thread.
+fn set_error(code: c_int, message: impl ToString) -> c_int {
+    let message = message.to_string().replace('\0', "\\0");
+    LAST_ERROR.with(|error| *error.borrow_mut() = CString::new(message).ok());
+    code
+}
+
+/// Runs `f`, turning a panic into `PY_REGEX_ERROR_PANIC`.
+fn guard(f: impl FnOnce() -> c_int) -> c_int {
+    guard_or(PY_REGEX_ERROR_PANIC, f)
+}
+
+/// Runs `f`, returning `panicked` if it panics. The error is reported as `PY_REGEX_ERROR_PANIC`.
+fn guard_or<T>(panicked: T, f: impl FnOnce() -> T) -> T {
+    LAST_ERROR.with(|error| error.borrow_mut().take());
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        set_error(PY_REGEX_ERROR_PANIC, "py-regex panicked");
+        panicked
+    })
+}
+
+/// Borrows a NUL-terminated UTF-8 string.
+///
+/// # Safety
+///
+/// `text` must be null or point to a NUL-terminated string that outlives `'a`.
+unsafe fn borrow_str<'a>(text: *const c_char) -> Result<&'a str, c_int> {
+    if text.is_null() {
+        return Err(set_error(PY_REGEX_ERROR_ARGUMENT, "null string"));
+    }
+    unsafe { CStr::from_ptr(text) }
+        .to_str()
+        .map_err(|err| set_error(PY_REGEX_ERROR_UTF8, err))
+}
+
+/// Compiles `pattern` with the given `Flags` bits and stores the new pattern in `*out`.
+///
+/// # Safety
+///
+/// `pattern` must be a NUL-terminated string and `out` a valid pointer to write to.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn py_regex_compile(
+    pattern: *const c_char,
+    flags: u32,
+    out: *mut *mut PyRegexHandle,
+) -> c_int {
+    guard(|| {
+        if out.is_null() {
+            return set_error(PY_REGEX_ERROR_ARGUMENT, "null output pointer");
+        }
+        let pattern = match unsafe { borrow_str(pattern) } {
+            Ok(pattern) => pattern,
+            Err(code) => return code,
+        };
+        match PyRegex::with_flags(pattern, Flags::from_bits(flags)) {
+            Ok(re) => {
+                unsafe { *out = Box::into_raw(Box::new(PyRegexHandle { re })) };
+                PY_REGEX_OK
+            }
+            Err(err) => set_error(PY_REGEX_ERROR_PYTHON, err),
+        }
+    })
+}
+
+/// Searches `text` for the first match. On a match, stores it in `*out` and returns
+/// `PY_REGEX_OK`; otherwise stores null and returns `PY_REGEX_NO_MATCH`. Returns
+/// `PY_REGEX_ERROR_PYTHON` if a group span does not fit `text`.
+///
+/// # Safety
+///
+/// `re` must come from `py_regex_compile` and not be freed, `text` must be a NUL-terminated
+/// string and `out` a valid pointer to write to.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn py_regex_search(
+    re: *const PyRegexHandle,
+    text: *const c_char,
+    out: *mut *mut PyRegexMatchHandle,
+) -> c_int {
+    guard(|| {
+        if re.is_null() || out.is_null() {
+            return set_error(PY_REGEX_ERROR_ARGUMENT, "null pointer");
+        }
+        unsafe { *out = ptr::null_mut() };
+        let text = match unsafe { borrow_str(text) } {
+            Ok(text) => text,
+            Err(code) => return code,
+        };
+        let data = match unsafe { &(*re).re }.search_match(text) {
+            Ok(Some(m)) => m.to_data(),
+            Ok(None) => return PY_REGEX_NO_MATCH,
+            Err(err) => Err(err),
+        };
+        let data = match data {
+            Ok(data) => data,
+            Err(err) => return set_error(PY_REGEX_ERROR_PYTHON, err),
+        };
+        let groups = match byte_groups(text, &data) {
+            Ok(groups) => groups,
+            Err(message) => return set_error(PY_REGEX_ERROR_PYTHON, message),
+        };
+        let names = data
+            .names()
+            .iter()
+            .map(|(name, &group)| (name.clone(), group))
+            .collect();
+        unsafe { *out = Box::into_raw(Box::new(PyRegexMatchHandle { groups, names })) };
+        PY_REGEX_OK
+    })
+}
+
+/// Converts the groups of a match in `text` to NUL-terminated strings with byte spans. Fails
+/// instead of reporting the group as not participating if a span does not fit `text`.
+fn byte_groups(text: &str, data: &MatchData) -> Result<Vec<ByteGroup>, String> {
+    (0..data.len())
+        .map(|group| {
+            let (Some(captured), Some(span)) = (data.get(group), data.span(group)) else {
+                return Ok(None);
+            };
+            let start = char_to_byte_offset(text, span.start)
+                .filter(|&start| text[start..].starts_with(captured))
+                .ok_or_else(|| format!("group {group} span {span:?} does not fit the text"))?;
+            let end = start + captured.len();
+            // The haystack has no interior NUL, so neither do the groups.
+            let captured = CString::new(captured).map_err(|err| err.to_string())?;
+            Ok(Some((captured, start..end)))
+        })
+        .collect()
+}
+
+/// Returns the number of groups of the match, including group 0.
+///
+/// # Safety
+///
+/// `m` must come from `py_regex_search` and not be freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn py_regex_match_len(m: *const PyRegexMatchHandle) -> usize {
+    unsafe { m.as_ref() }.map_or(0, |m| m.groups.len())
+}
+
+/// Stores the NUL-terminated text of `group` in `*text` and its byte span in `*start` and `*end`
+/// (any of which may be null). Returns `PY_REGEX_NO_GROUP` if the group did not participate.
+///
+/// # Safety
+///
+/// `m` must come from `py_regex_search` and not be freed; the output pointers must be null or
+/// valid to write to.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn py_regex_match_group(
+    m: *const PyRegexMatchHandle,
+    group: usize,
+    text: *mut *const c_char,
+    start: *mut usize,
+    end: *mut usize,
+) -> c_int {
+    guard(|| {
+        let Some(m) = (unsafe { m.as_ref() }) else {
+            return set_error(PY_REGEX_ERROR_ARGUMENT, "null match");
+        };
+        let Some(slot) = m.groups.get(group) else {
+            return set_error(PY_REGEX_ERROR_ARGUMENT, format!("no such group: {group}"));
+        };
+        let Some((captured, span)) = slot else {
+            return PY_REGEX_NO_GROUP;
+        };
+        unsafe {
+            if !text.is_null() {
+                *text = captured.as_ptr();
+            }
+            if !start.is_null() {
+                *start = span.start;
+            }
+            if !end.is_null() {
+                *end = span.end;
+            }
+        }
+        PY_REGEX_OK
+    })
+}
+
+/// Returns the number of the named group, or -1 if there is no group with that name or an
+/// argument is invalid, in which case `py_regex_last_error` describes the error.
+///
+/// # Safety
+///
+/// `m` must come from `py_regex_search` and not be freed, and `name` must be a NUL-terminated
+/// string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn py_regex_match_group_index(
+    m: *const PyRegexMatchHandle,
+    name: *const c_char,
+) -> isize {
+    guard_or(-1, || {
+        let Some(m) = (unsafe { m.as_ref() }) else {
+            set_error(PY_REGEX_ERROR_ARGUMENT, "null match");
+            return -1;
+        };
+        let Ok(name) = (unsafe { borrow_str(name) }) else {
+            return -1;
+        };
+        match m.names.iter().find(|(candidate, _)| candidate == name) {
+            Some(&(_, group)) => group as isize,
+            None => {
+                set_error(PY_REGEX_ERROR_ARGUMENT, format!("no such group: {name}"));
+                -1
+            }
+        }
+    })
+}
+
+/// Returns the message of the last error on this thread, or null if the last call succeeded.
+#[unsafe(no_mangle)]
+pub extern "C" fn py_regex_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Frees a pattern returned by `py_regex_compile`. Null is ignored.
+///
+/// # Safety
+///
+/// `re` must be null or come from `py_regex_compile` and not be freed already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn py_regex_free(re: *mut PyRegexHandle) {
+    if !re.is_null() {
+        drop(unsafe { Box::from_raw(re) });
+    }
+}
+
+/// Frees a match returned by `py_regex_search`. Null is ignored.
+///
+/// # Safety
+///
+/// `m` must be null or come from `py_regex_search` and not be freed already.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn py_regex_match_free(m: *mut PyRegexMatchHandle) {
+    if !m.is_null() {
+        drop(unsafe { Box::from_raw(m) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi() {
+        pyo3::prepare_freethreaded_python();
+
+        unsafe {
+            let mut re = ptr::null_mut();
+            assert_eq!(
+                py_regex_compile(c"(?P<key>\\w+)=(\\d+)?".as_ptr(), 0, &mut re),
+                PY_REGEX_OK
+            );
+            let mut m = ptr::null_mut();
+            assert_eq!(
+                py_regex_search(re, c"\u{e9} k=".as_ptr(), &mut m),
+                PY_REGEX_OK
+            );
+            assert_eq!(py_regex_match_len(m), 3);
+            assert_eq!(py_regex_match_group_index(m, c"key".as_ptr()), 1);
+            let (mut text, mut start, mut end) = (ptr::null(), 0, 0);
+            assert_eq!(
+                py_regex_match_group(m, 1, &mut text, &mut start, &mut end),
+                PY_REGEX_OK
+            );
+            assert_eq!((CStr::from_ptr(text), start, end), (c"k", 3, 4));
+            assert_eq!(
+                py_regex_match_group(m, 2, &mut text, &mut start, &mut end),
+                PY_REGEX_NO_GROUP
+            );
+            py_regex_match_free(m);
+
+            assert_eq!(
+                py_regex_search(re, c"-".as_ptr(), &mut m),
+                PY_REGEX_NO_MATCH
+            );
+            assert!(m.is_null());
+            py_regex_free(re);
+
+            assert_eq!(
+                py_regex_compile(c"(".as_ptr(), 0, &mut re),
+                PY_REGEX_ERROR_PYTHON
+            );
+            assert!(!py_regex_last_error().is_null());
+        }
+
+        let data = MatchData::new(vec![Some(("b".into(), 5..6))], Default::default());
+        assert!(byte_groups("ab", &data).is_err());
+        let data = MatchData::new(vec![Some(("b".into(), 0..1))], Default::default());
+        assert!(byte_groups("ab", &data).is_err());
+        let data = MatchData::new(vec![Some(("b".into(), 1..2)), None], Default::default());
+        assert_eq!(
+            byte_groups("éb", &data),
+            Ok(vec![Some((c"b".into(), 2..3)), None])
+        );
+    }
+
+    #[test]
+    fn test_ffi_match_group_index() {
+        pyo3::prepare_freethreaded_python();
+
+        unsafe {
+            let mut re = ptr::null_mut();
+            let pattern = c"(?P<key>\\w+)=(?P<value>\\d+)";
+            assert_eq!(py_regex_compile(pattern.as_ptr(), 0, &mut re), PY_REGEX_OK);
+            let mut m = ptr::null_mut();
+            assert_eq!(py_regex_search(re, c"k=1".as_ptr(), &mut m), PY_REGEX_OK);
+
+            assert_eq!(py_regex_match_group_index(m, c"missing".as_ptr()), -1);
+            assert!(!py_regex_last_error().is_null());
+            // A successful call clears the previous error.
+            assert_eq!(py_regex_match_group_index(m, c"value".as_ptr()), 2);
+            assert!(py_regex_last_error().is_null());
+            assert_eq!(py_regex_match_group_index(ptr::null(), c"key".as_ptr()), -1);
+            assert_eq!(py_regex_match_group_index(m, ptr::null()), -1);
+            assert!(!py_regex_last_error().is_null());
+
+            py_regex_match_free(m);
+            py_regex_free(re);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::haystack::PyText;
+use crate::helpers::helpers;
+use crate::python::{PyRegex, PyRegexMatch};
+use pyo3::prelude::*;
+use std::ops::Range;
+
+/// Conditions on the matches returned by `PyRegex::find_iter_filtered`, with lengths and
+/// positions in code points.
+///
+/// ```
+/// use py_regex::{MatchFilter, PyRegex};
+///
+/// let filter = MatchFilter { min_len: 3, within: Some(0..1000), ..Default::default() };
+/// let words = PyRegex::new(r"\w+")?.find_iter_filtered("a bb ccc dddd", &filter)?;
+/// assert_eq!(words.len(), 2);
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchFilter {
+    /// Shortest accepted match.
+    pub min_len: usize,
+    /// Longest accepted match, if limited.
+    pub max_len: Option<usize>,
+    /// Part of the text to search, as with the `pos` and `endpos` arguments of Python's
+    /// `finditer`: `^` and `$` still refer to the whole text, but `$` also matches at the end
+    /// of the range.
+    pub within: Option<Range<usize>>,
+}
+
+impl PyRegex {
+    /// Returns the non-overlapping matches that pass the filter. Rejected matches are dropped on
+    /// the Python side, so no `PyRegexMatch` is created for them.
+    pub fn find_iter_filtered<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+        filter: &MatchFilter,
+    ) -> PyResult<Vec<PyRegexMatch>> {
+        Python::with_gil(|py| {
+            let (pos, endpos) = filter
+                .within
+                .as_ref()
+                .map_or((0, isize::MAX as usize), |within| {
+                    (within.start, within.end)
+                });
+            helpers(py)?
+                .call_method1(
+                    "find_filtered",
+                    (
+                        self.as_py(),
+                        text.to_py_str(py),
+                        pos,
+                        endpos,
+                        filter.min_len,
+                        filter.max_len.unwrap_or(isize::MAX as usize),
+                        self.kwargs(py),
+                    ),
+                )?
+                .try_iter()?
+                .map(|m| Ok(PyRegexMatch::new(m?.unbind())))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_iter_filtered() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\w+")?;
+        let texts = |filter| -> PyResult<Vec<String>> {
+            re.find_iter_filtered("a bb ccc dddd eeeee", &filter)?
+                .iter()
+                .map(|m| Ok(m.group(0)?.unwrap_or_default()))
+                .collect()
+        };
+        assert_eq!(
+            texts(MatchFilter {
+                min_len: 2,
+                max_len: Some(4),
+                ..Default::default()
+            })?,
+            ["bb", "ccc", "dddd"]
+        );
+        assert_eq!(
+            texts(MatchFilter {
+                within: Some(3..10),
+                ..Default::default()
+            })?,
+            ["b", "ccc", "d"]
+        );
+
+        Ok(())
+    }
+}
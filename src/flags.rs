@@ -0,0 +1,145 @@
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+
+/// Compilation flags, mirroring the flag constants of the Python `regex` module.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Flags(u32);
+
+impl Flags {
+    /// No flags set.
+    pub const NONE: Flags = Flags(0);
+    /// `regex.ASCII`: make `\w`, `\b`, `\d` and `\s` match ASCII characters only.
+    pub const ASCII: Flags = Flags(0x80);
+    /// `regex.IGNORECASE`: perform case-insensitive matching.
+    pub const IGNORECASE: Flags = Flags(0x2);
+    /// `regex.LOCALE`: make `\w`, `\b` and `\s` depend on the current locale.
+    pub const LOCALE: Flags = Flags(0x4);
+    /// `regex.MULTILINE`: make `^` and `$` match at the beginning and end of each line.
+    pub const MULTILINE: Flags = Flags(0x8);
+    /// `regex.DOTALL`: make `.` match any character, including a newline.
+    pub const DOTALL: Flags = Flags(0x10);
+    /// `regex.UNICODE`: make `\w`, `\b`, `\d` and `\s` match Unicode characters.
+    pub const UNICODE: Flags = Flags(0x20);
+    /// `regex.VERBOSE`: allow whitespace and comments in the pattern.
+    pub const VERBOSE: Flags = Flags(0x40);
+    /// `regex.POSIX`: return the leftmost longest match instead of the first alternative that matches.
+    pub const POSIX: Flags = Flags(0x10000);
+    /// `regex.WORD`: use Unicode default word boundaries for `\b` and treat any line separator
+    /// as a line break.
+    pub const WORD: Flags = Flags(0x800);
+    /// `regex.FULLCASE`: use full case folding with `IGNORECASE`, so that `ß` matches `ss`.
+    pub const FULLCASE: Flags = Flags(0x4000);
+    /// `regex.BESTMATCH`: make fuzzy searches return the best match instead of the first one.
+    pub const BESTMATCH: Flags = Flags(0x1000);
+    /// `regex.ENHANCEMATCH`: make fuzzy matches improve the fit of the match they found.
+    pub const ENHANCEMATCH: Flags = Flags(0x8000);
+    /// `regex.REVERSE`: search backwards, from the end of the text to the start.
+    pub const REVERSE: Flags = Flags(0x400);
+    /// `regex.VERSION0`: behave like the standard `re` module.
+    pub const VERSION0: Flags = Flags(0x2000);
+    /// `regex.VERSION1`: enable nested sets, set operations and scoped inline flags.
+    /// Case-insensitive matching then uses full case folding.
+    pub const VERSION1: Flags = Flags(0x100);
+
+    /// Names of the flags, used by the `Debug` implementation.
+    const NAMED: &[(&'static str, Flags)] = &[
+        ("ASCII", Flags::ASCII),
+        ("IGNORECASE", Flags::IGNORECASE),
+        ("LOCALE", Flags::LOCALE),
+        ("MULTILINE", Flags::MULTILINE),
+        ("DOTALL", Flags::DOTALL),
+        ("UNICODE", Flags::UNICODE),
+        ("VERBOSE", Flags::VERBOSE),
+        ("POSIX", Flags::POSIX),
+        ("WORD", Flags::WORD),
+        ("FULLCASE", Flags::FULLCASE),
+        ("BESTMATCH", Flags::BESTMATCH),
+        ("ENHANCEMATCH", Flags::ENHANCEMATCH),
+        ("REVERSE", Flags::REVERSE),
+        ("VERSION0", Flags::VERSION0),
+        ("VERSION1", Flags::VERSION1),
+    ];
+
+    /// Creates flags from the raw integer value used by the Python `regex` module.
+    pub const fn from_bits(bits: u32) -> Self {
+        Flags(bits)
+    }
+
+    /// Returns the raw integer value passed to the Python `regex` module.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if all flags in `other` are set.
+    pub const fn contains(self, other: Flags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the flags in `self` that are not set in `other`.
+    pub const fn difference(self, other: Flags) -> Flags {
+        Flags(self.0 & !other.0)
+    }
+}
+
+/// Behaviour versions of the `regex` module, selected per pattern with `Flags::VERSION0` and
+/// `Flags::VERSION1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Version {
+    /// Compatible with the standard `re` module; the module's default.
+    #[default]
+    V0,
+    /// Nested sets, set operations and scoped inline flags.
+    V1,
+}
+
+impl Version {
+    /// Returns the flag that selects this version.
+    pub const fn flags(self) -> Flags {
+        match self {
+            Version::V0 => Flags::VERSION0,
+            Version::V1 => Flags::VERSION1,
+        }
+    }
+}
+
+impl fmt::Debug for Flags {
+    /// Formats the flags as in Python, e.g. `IGNORECASE | MULTILINE`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rest = self.0;
+        let mut first = true;
+        for (name, flag) in Flags::NAMED {
+            if self.contains(*flag) && flag.0 != 0 {
+                f.write_str(if first { "" } else { " | " })?;
+                f.write_str(name)?;
+                rest &= !flag.0;
+                first = false;
+            }
+        }
+        if rest != 0 {
+            f.write_str(if first { "" } else { " | " })?;
+            write!(f, "{rest:#x}")?;
+        } else if first {
+            f.write_str("NONE")?;
+        }
+        Ok(())
+    }
+}
+
+impl BitOr for Flags {
+    type Output = Flags;
+
+    fn bitor(self, rhs: Flags) -> Flags {
+        Flags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Flags {
+    fn bitor_assign(&mut self, rhs: Flags) {
+        self.0 |= rhs.0;
+    }
+}
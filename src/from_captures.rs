@@ -0,0 +1,131 @@
+use crate::match_data::MatchData;
+use pyo3::PyResult;
+use pyo3::exceptions::PyValueError;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Types that can be built from a match, usually from its named groups with
+/// `#[derive(FromCaptures)]` (requires the `macros` feature):
+///
+/// ```ignore
+/// #[derive(FromCaptures)]
+/// struct LogLine {
+///     ts: String,
+///     level: Level,        // any `FromStr` type
+///     #[capture(name = "message")]
+///     msg: String,
+///     pid: Option<u32>,    // `None` if the group did not participate
+/// }
+///
+/// let line: Option<LogLine> = re.captures_as(text)?;
+/// ```
+pub trait FromCaptures: Sized {
+    /// Builds the value from the groups of a match.
+    fn from_captures(data: &MatchData) -> PyResult<Self>;
+}
+
+/// Builds a tuple from groups 1 to N, in order, each parsed with `FromStr`, as in
+/// `let (key, count) = m.extract::<(String, u32)>()?`. Fails if the pattern does not have exactly
+/// N groups, if a group did not participate, or if one cannot be parsed, naming the group.
+macro_rules! impl_from_captures_for_tuple {
+    ($len:literal: $($ty:ident $group:literal),+) => {
+        impl<$($ty),+> FromCaptures for ($($ty,)+)
+        where
+            $($ty: FromStr, $ty::Err: Display,)+
+        {
+            fn from_captures(data: &MatchData) -> PyResult<Self> {
+                if data.len() != $len + 1 {
+                    return Err(PyValueError::new_err(format!(
+                        "expected a pattern with {} groups, found {}",
+                        $len,
+                        data.len() - 1
+                    )));
+                }
+                Ok(($(
+                    parse_capture::<$ty>(
+                        data.get($group)
+                            .ok_or_else(|| missing_group(stringify!($group)))?,
+                        $group,
+                    )?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_from_captures_for_tuple!(1: T1 1);
+impl_from_captures_for_tuple!(2: T1 1, T2 2);
+impl_from_captures_for_tuple!(3: T1 1, T2 2, T3 3);
+impl_from_captures_for_tuple!(4: T1 1, T2 2, T3 3, T4 4);
+impl_from_captures_for_tuple!(5: T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_from_captures_for_tuple!(6: T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_from_captures_for_tuple!(7: T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+impl_from_captures_for_tuple!(8: T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+impl_from_captures_for_tuple!(9: T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+impl_from_captures_for_tuple!(10: T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+impl_from_captures_for_tuple!(11: T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);
+impl_from_captures_for_tuple!(12: T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11, T12 12);
+
+/// Parses the named group with `FromStr`; used by `#[derive(FromCaptures)]`.
+#[doc(hidden)]
+pub fn parse_group<T>(data: &MatchData, name: &str) -> PyResult<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    data.name(name)
+        .map(|text| parse_capture(text, name))
+        .transpose()
+}
+
+/// Parses the text of a group with `FromStr`, naming the group in the error.
+pub(crate) fn parse_capture<T>(text: &str, group: impl Display) -> PyResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    text.parse().map_err(|err| {
+        PyValueError::new_err(format!("cannot parse group `{group}` ({text:?}): {err}"))
+    })
+}
+
+/// Returns the error for a required group that did not participate in the match.
+#[doc(hidden)]
+pub fn missing_group(name: &str) -> pyo3::PyErr {
+    PyValueError::new_err(format!("group `{name}` did not participate in the match"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PyRegex;
+    use pyo3::PyResult;
+
+    #[test]
+    fn test_extract_tuple() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(\w+)=(\d+)(?:;(\w+))?")?;
+        let m = re.search_match("count=7;x")?.unwrap();
+        let (key, count, rest) = m.extract::<(String, u32, char)>()?;
+        assert_eq!((key.as_str(), count, rest), ("count", 7, 'x'));
+        assert!(m.extract::<(String, u32)>().is_err());
+
+        let m = re.search_match("count=7")?.unwrap();
+        let missing = m.extract::<(String, u32, String)>().unwrap_err();
+        assert!(
+            missing
+                .to_string()
+                .contains("group `3` did not participate")
+        );
+        let invalid = re.search_match("a=99999999999;b")?.unwrap();
+        let err = invalid.extract::<(String, u32, String)>().unwrap_err();
+        assert!(err.to_string().contains("cannot parse group `2`"));
+
+        assert_eq!(
+            re.captures_as::<(String, u8, String)>("k=1;v")?.unwrap().1,
+            1
+        );
+
+        Ok(())
+    }
+}
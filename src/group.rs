@@ -0,0 +1,86 @@
+use std::fmt;
+
+/// A group of a match, by number or by name, accepted by the `PyRegexMatch` accessors as
+/// `impl Into<Group>`, so that `m.group(1)` and `m.group("word")` both work as in Python.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Group<'a> {
+    /// A group by number, 0 for the whole match.
+    Index(usize),
+    /// A named group.
+    Name(&'a str),
+}
+
+/// Implements `From` for the integer types group numbers are commonly held in.
+macro_rules! impl_from_index {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Group<'_> {
+                fn from(group: $ty) -> Self {
+                    Group::Index(group as usize)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_index!(u8, u16, u32, usize);
+
+/// Unsuffixed literals such as `m.group(1)` are `i32`. A negative number refers to no group, so
+/// the accessors fail with an `IndexError` as in Python.
+impl From<i32> for Group<'_> {
+    fn from(group: i32) -> Self {
+        Group::Index(usize::try_from(group).unwrap_or(usize::MAX))
+    }
+}
+
+impl<'a> From<&'a str> for Group<'a> {
+    fn from(name: &'a str) -> Self {
+        Group::Name(name)
+    }
+}
+
+impl<'a> From<&'a String> for Group<'a> {
+    fn from(name: &'a String) -> Self {
+        Group::Name(name)
+    }
+}
+
+impl fmt::Display for Group<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Group::Index(group) => group.fmt(f),
+            Group::Name(name) => name.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> pyo3::IntoPyObject<'py> for Group<'_> {
+    type Target = pyo3::PyAny;
+    type Output = pyo3::Bound<'py, pyo3::PyAny>;
+    type Error = std::convert::Infallible;
+
+    fn into_pyobject(self, py: pyo3::Python<'py>) -> Result<Self::Output, Self::Error> {
+        Ok(match self {
+            Group::Index(group) => group.into_pyobject(py)?.into_any(),
+            Group::Name(name) => pyo3::types::PyString::new(py, name).into_any(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_from_integers() {
+        let (small, medium, large, index) = (1u8, 2u16, 3u32, 4usize);
+        assert_eq!(Group::from(small), Group::Index(1));
+        assert_eq!(Group::from(medium), Group::Index(2));
+        assert_eq!(Group::from(large), Group::Index(3));
+        assert_eq!(Group::from(index), Group::Index(4));
+        assert_eq!(Group::from(5), Group::Index(5));
+        assert_eq!(Group::from(-1), Group::Index(usize::MAX));
+        assert_eq!(Group::from("word"), Group::Name("word"));
+    }
+}
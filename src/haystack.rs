@@ -0,0 +1,280 @@
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use std::borrow::Cow;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Text that patterns can search.
+///
+/// Rust strings are copied into a new Python `str` on every call. Code that already holds Python
+/// strings, e.g. values returned by a Python API, can pass them as they are with no conversion.
+pub trait PyText {
+    /// Returns the text as a Python `str`.
+    fn to_py_str<'py>(&self, py: Python<'py>) -> Bound<'py, PyString>;
+
+    /// Returns the length of the text, in bytes for Rust strings and in code points for Python
+    /// strings. Only used for instrumentation.
+    fn text_len(&self) -> usize;
+}
+
+impl PyText for str {
+    fn to_py_str<'py>(&self, py: Python<'py>) -> Bound<'py, PyString> {
+        PyString::new(py, self)
+    }
+
+    fn text_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Implements `PyText` for owned and shared strings, so `&String`, `&Cow<str>` or `&Arc<str>`
+/// can be passed without `.as_str()`.
+macro_rules! impl_py_text_for_str {
+    ($($ty:ty),*) => {
+        $(
+            impl PyText for $ty {
+                fn to_py_str<'py>(&self, py: Python<'py>) -> Bound<'py, PyString> {
+                    PyString::new(py, self)
+                }
+
+                fn text_len(&self) -> usize {
+                    self.len()
+                }
+            }
+        )*
+    };
+}
+
+impl_py_text_for_str!(String, Box<str>, Rc<str>, Arc<str>, Cow<'_, str>);
+
+impl PyText for Bound<'_, PyString> {
+    fn to_py_str<'py>(&self, py: Python<'py>) -> Bound<'py, PyString> {
+        self.as_unbound().bind(py).clone()
+    }
+
+    fn text_len(&self) -> usize {
+        self.len().unwrap_or_default()
+    }
+}
+
+impl PyText for Py<PyString> {
+    fn to_py_str<'py>(&self, py: Python<'py>) -> Bound<'py, PyString> {
+        self.bind(py).clone()
+    }
+
+    fn text_len(&self) -> usize {
+        Python::with_gil(|py| self.bind(py).text_len())
+    }
+}
+
+/// Byte strings that are conventionally UTF-8. They are decoded with Python's `surrogateescape`,
+/// so each byte of an invalid sequence is searched as one lone surrogate and matching never fails
+/// on malformed input. Positions reported by Python map back to the bytes with
+/// `bstr_char_to_byte_offset`, and `replace_bytes`, `find_all_bytes` and `group_bytes` return the
+/// original bytes, while methods returning `String` fail on text with invalid UTF-8.
+#[cfg(feature = "bstr")]
+impl PyText for bstr::BStr {
+    fn to_py_str<'py>(&self, py: Python<'py>) -> Bound<'py, PyString> {
+        if let Ok(text) = std::str::from_utf8(self) {
+            return PyString::new(py, text);
+        }
+        pyo3::types::PyBytes::new(py, self)
+            .call_method1("decode", ("utf-8", "surrogateescape"))
+            .and_then(|text| Ok(text.downcast_into::<PyString>()?))
+            .expect("decoding with surrogateescape does not fail")
+    }
+
+    fn text_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "bstr")]
+impl PyText for bstr::BString {
+    fn to_py_str<'py>(&self, py: Python<'py>) -> Bound<'py, PyString> {
+        bstr::BStr::new(self).to_py_str(py)
+    }
+
+    fn text_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// A text converted into a Python `str` once, to be searched by many patterns without copying
+/// it into Python again for each of them.
+///
+/// ```
+/// use py_regex::{Haystack, PyRegex};
+///
+/// let rules = [PyRegex::new(r"\berror\b")?, PyRegex::new(r"\btimeout\b")?];
+/// let text = Haystack::new("request failed: timeout after 30s");
+/// let hits = rules.iter().filter(|re| re.is_match(&text).unwrap_or(false)).count();
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+pub struct Haystack {
+    text: Py<PyString>,
+    len: usize,
+}
+
+impl Haystack {
+    /// Copies the text into a Python `str`.
+    pub fn new(text: &str) -> Self {
+        Haystack {
+            text: Python::with_gil(|py| PyString::new(py, text).unbind()),
+            len: text.len(),
+        }
+    }
+
+    /// Returns the Python `str`.
+    pub fn as_py(&self) -> &Py<PyString> {
+        &self.text
+    }
+}
+
+impl Clone for Haystack {
+    /// Clones the handle; both refer to the same Python string.
+    fn clone(&self) -> Self {
+        Python::with_gil(|py| Haystack {
+            text: self.text.clone_ref(py),
+            len: self.len,
+        })
+    }
+}
+
+impl fmt::Debug for Haystack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Haystack")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<&str> for Haystack {
+    fn from(text: &str) -> Self {
+        Haystack::new(text)
+    }
+}
+
+impl PyText for Haystack {
+    fn to_py_str<'py>(&self, py: Python<'py>) -> Bound<'py, PyString> {
+        self.text.bind(py).clone()
+    }
+
+    /// Returns the length in bytes of the original text.
+    fn text_len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PyRegex;
+
+    #[test]
+    fn test_python_string_haystack() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\d+")?;
+        let owned: Py<PyString> = Python::with_gil(|py| {
+            let text = PyString::new(py, "a1 b22");
+            assert_eq!(re.find_all(&text)?, ["1", "22"]);
+            Ok::<_, PyErr>(text.unbind())
+        })?;
+        assert_eq!(re.replace(&owned, "N")?, "aN bN");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_owned_and_shared_strings() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\d+")?;
+        assert!(re.is_match(&String::from("7"))?);
+        assert!(re.is_match(&Cow::Borrowed("7"))?);
+        assert_eq!(re.find_all(&Arc::<str>::from("8 9"))?, ["8", "9"]);
+        assert_eq!(re.replace(&Box::<str>::from("a1"), "N")?, "aN");
+        assert_eq!(re.find_all(&Rc::<str>::from("10"))?, ["10"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_haystack() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let haystack = Haystack::new("a1 b22");
+        assert_eq!(PyRegex::new(r"\d+")?.find_iter(&haystack)?.len(), 2);
+        assert_eq!(PyRegex::new("b")?.split(&haystack)?, ["a1 ", "22"]);
+        let clone = haystack.clone();
+        assert!(clone.as_py().is(haystack.as_py()));
+        assert_eq!(clone.text_len(), 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_py_text_helpers() -> PyResult<()> {
+        use crate::{Lexer, Memoized, Policy, Redactor, highlight_all};
+        pyo3::prepare_freethreaded_python();
+
+        let haystack = Haystack::new("é1 b22");
+        let re = PyRegex::new(r"\d+")?;
+        assert_eq!(
+            re.match_indices(&haystack)?.collect::<Vec<_>>(),
+            [(2, "1".to_string()), (5, "22".to_string())]
+        );
+        assert_eq!(re.split_keep(&haystack)?, ["é", "1", " b", "22", ""]);
+        assert_eq!(re.split_discard(&haystack)?, ["é", " b", ""]);
+
+        let lexer = Lexer::new([("number", r"\d+"), ("word", r"[^\d ]+"), ("space", " ")])?;
+        assert_eq!(lexer.tokenize(&haystack)?.len(), 5);
+        let redactor = Redactor::new([(r"\d+", Policy::Mask('#'))])?;
+        assert_eq!(redactor.redact(&haystack)?.text, "é# b##");
+        assert_eq!(
+            highlight_all(std::slice::from_ref(&re), &haystack)?.len(),
+            2
+        );
+
+        let memoized = Memoized::new(re, 8);
+        assert!(memoized.is_match(&String::from("7"))?);
+        assert_eq!(memoized.find_all(&Arc::<str>::from("8 9"))?, ["8", "9"]);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "bstr")]
+    #[test]
+    fn test_bstr_haystack() -> PyResult<()> {
+        use crate::bstr_char_to_byte_offset;
+        use bstr::{B, BStr};
+        pyo3::prepare_freethreaded_python();
+
+        let text = BStr::new(B(b"caf\xc3\xa9 \xff\xf0\x9f\x98 \xef\xbf\xbd id=42"));
+        let re = PyRegex::new(r"id=(\d+)")?;
+        let m = re.search_match(text)?.unwrap();
+        assert_eq!(m.group(1)?.as_deref(), Some("42"));
+        let start = bstr_char_to_byte_offset(text, m.start_pos(1)?.unwrap()).unwrap();
+        assert_eq!(&text[start..], b"42");
+        // Invalid bytes survive a round trip instead of becoming U+FFFD.
+        assert_eq!(
+            re.replace_bytes(text, "N")?,
+            B(b"caf\xc3\xa9 \xff\xf0\x9f\x98 \xef\xbf\xbd N")
+        );
+        assert!(re.replace(text, "N").is_err());
+        let words = PyRegex::new(r"\S+")?.find_all_bytes(text)?;
+        assert_eq!(words[1], B(b"\xff\xf0\x9f\x98"));
+        let m = PyRegex::new(r"\s(\S+)\s")?.search_match(text)?.unwrap();
+        assert_eq!(m.group_bytes(1)?.unwrap(), B(b"\xff\xf0\x9f\x98"));
+        let span = m.span(1)?.unwrap();
+        let end = bstr_char_to_byte_offset(text, span.end).unwrap();
+        assert_eq!(
+            &text[bstr_char_to_byte_offset(text, span.start).unwrap()..end],
+            b"\xff\xf0\x9f\x98"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+"""Python-side helpers that do in one call what would otherwise take one FFI round trip per item."""
+
+import collections
+import itertools
+
+
+def find_filtered(compiled, text, pos, endpos, min_len, max_len, kwargs):
+    return [
+        m
+        for m in compiled.finditer(text, pos, endpos, **(kwargs or {}))
+        if min_len <= m.end() - m.start() <= max_len
+    ]
+
+
+def match_data(m):
+    return (m.group(0),) + m.groups(), m.regs, dict(m.re.groupindex)
+
+
+def preview_sub(compiled, template, text, kwargs):
+    return [(m.span(), m.group(), m.expand(template)) for m in compiled.finditer(text, **(kwargs or {}))]
+
+
+def find_nth(compiled, text, n, kwargs):
+    return next(itertools.islice(compiled.finditer(text, **(kwargs or {})), n, None), None)
+
+
+def find_last(compiled, text, kwargs):
+    last = collections.deque(compiled.finditer(text, **(kwargs or {})), maxlen=1)
+    return last[0] if last else None
+
+
+def join_matches(compiled, text, separator, kwargs):
+    return separator.join(m.group() for m in compiled.finditer(text, **(kwargs or {})))
+
+
+def unique_matches(compiled, text, fold_case, flags, kwargs):
+    seen = {}
+    for m in compiled.finditer(text, **(kwargs or {})):
+        found = m.group()
+        seen.setdefault(fold_case(flags, found) if fold_case else found, found)
+    return list(seen.values())
+
+
+def group_matches(compiled, text, name, kwargs):
+    return [
+        (m.group(name), match_data(m))
+        for m in compiled.finditer(text, **(kwargs or {}))
+        if m.span(name) != (-1, -1)
+    ]
+
+
+def match_stats(compiled, text, kwargs):
+    names = compiled.groupindex
+    participation = dict.fromkeys(names, 0)
+    count = total = 0
+    shortest = longest = None
+    for m in compiled.finditer(text, **(kwargs or {})):
+        length = m.end() - m.start()
+        count += 1
+        total += length
+        shortest = length if shortest is None else min(shortest, length)
+        longest = length if longest is None else max(longest, length)
+        regs = m.regs
+        for name, index in names.items():
+            if regs[index][0] != -1:
+                participation[name] += 1
+    return count, total, shortest, longest, participation
+
+
+def find_longest(patterns, text, pos, kwargs):
+    best = best_key = None
+    for index, (compiled, options) in enumerate(zip(patterns, kwargs)):
+        m = compiled.search(text, pos, **(options or {}))
+        if m is not None and (best is None or (m.start(), m.start() - m.end()) < best_key):
+            best, best_key = (index, m), (m.start(), m.start() - m.end())
+    return best
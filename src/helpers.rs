@@ -0,0 +1,59 @@
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+use std::ffi::CStr;
+use std::sync::Mutex;
+
+/// A Python module defined by source embedded in the crate, loaded on first use and dropped by
+/// `shutdown`.
+pub(crate) struct EmbeddedModule {
+    code: &'static CStr,
+    file_name: &'static CStr,
+    module_name: &'static CStr,
+    module: Mutex<Option<Py<PyModule>>>,
+}
+
+impl EmbeddedModule {
+    pub(crate) const fn new(
+        code: &'static CStr,
+        file_name: &'static CStr,
+        module_name: &'static CStr,
+    ) -> Self {
+        EmbeddedModule {
+            code,
+            file_name,
+            module_name,
+            module: Mutex::new(None),
+        }
+    }
+
+    /// Returns the module, loading it if needed.
+    pub(crate) fn get<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyModule>> {
+        if let Some(module) = self.module.lock().unwrap().as_ref() {
+            return Ok(module.bind(py).clone());
+        }
+        // Loading runs Python code, which may release the GIL, so the lock is not held meanwhile.
+        let module = PyModule::from_code(py, self.code, self.file_name, self.module_name)?;
+        let mut cached = self.module.lock().unwrap();
+        Ok(cached
+            .get_or_insert_with(|| module.unbind())
+            .bind(py)
+            .clone())
+    }
+
+    /// Forgets the module, so that the next `get` loads it again.
+    pub(crate) fn clear(&self) -> Option<Py<PyModule>> {
+        self.module.lock().unwrap().take()
+    }
+}
+
+/// The Python side of operations that batch work per match.
+pub(crate) static HELPERS: EmbeddedModule = EmbeddedModule::new(
+    pyo3::ffi::c_str!(include_str!("helpers.py")),
+    c"py_regex/helpers.py",
+    c"py_regex_helpers",
+);
+
+/// Returns the helper module defined in `helpers.py`.
+pub(crate) fn helpers(py: Python<'_>) -> PyResult<Bound<'_, PyModule>> {
+    HELPERS.get(py)
+}
@@ -0,0 +1,254 @@
+use crate::haystack::PyText;
+use crate::offsets::ByteOffsets;
+use crate::python::PyRegex;
+use pyo3::prelude::*;
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// A highlighted region of a text, as returned by `PyRegex::highlight` and `highlight_all`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The code point range of the region.
+    pub range: Range<usize>,
+    /// The index of the pattern that produced the region.
+    pub pattern_id: usize,
+    /// The group the region belongs to, 0 for parts of a match outside any capture group.
+    pub group: u16,
+}
+
+impl PyRegex {
+    /// Returns the regions of all matches in the text, labeled with the innermost group that
+    /// covers them, as sorted, non-overlapping spans with `pattern_id` 0.
+    pub fn highlight<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<Span>> {
+        highlight_all(std::slice::from_ref(self), text)
+    }
+
+    /// Returns the text with every match wrapped in the style's markers, e.g. to show matches in a
+    /// terminal. Empty matches are not marked.
+    ///
+    /// ```
+    /// use py_regex::{PyRegex, Style};
+    ///
+    /// let re = PyRegex::new(r"\d+")?;
+    /// assert_eq!(re.render("a1 b22", &Style::new("[", "]"))?, "a[1] b[22]");
+    /// # Ok::<(), pyo3::PyErr>(())
+    /// ```
+    pub fn render(&self, text: &str, style: &Style) -> PyResult<String> {
+        let mut spans = Vec::new();
+        for m in self.find_iter(text)? {
+            if let Some(range) = m.span(0)? {
+                spans.push(Span {
+                    range,
+                    pattern_id: 0,
+                    group: 0,
+                });
+            }
+        }
+        Ok(render(text, &spans, |_| style.clone()))
+    }
+}
+
+/// The markers that `render` puts around a region: ANSI escape sequences or arbitrary text such
+/// as HTML tags.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Style {
+    /// Inserted before the region.
+    pub open: Cow<'static, str>,
+    /// Inserted after the region.
+    pub close: Cow<'static, str>,
+}
+
+impl Style {
+    /// Bold red, as `grep --color` shows matches.
+    pub const MATCH: Style = Style::ansi_static("\x1b[1;31m");
+    /// Bold blue.
+    pub const BLUE: Style = Style::ansi_static("\x1b[1;34m");
+    /// Bold green.
+    pub const GREEN: Style = Style::ansi_static("\x1b[1;32m");
+    /// Black on yellow, like a highlighter pen.
+    pub const HIGHLIGHT: Style = Style::ansi_static("\x1b[30;43m");
+
+    /// Wraps regions in the given markers.
+    pub fn new(open: impl Into<Cow<'static, str>>, close: impl Into<Cow<'static, str>>) -> Self {
+        Style {
+            open: open.into(),
+            close: close.into(),
+        }
+    }
+
+    /// Wraps regions in an ANSI SGR sequence with the given parameters, such as `"1;31"` for bold
+    /// red, followed by a reset.
+    pub fn ansi(params: &str) -> Self {
+        Style::new(format!("\x1b[{params}m"), RESET)
+    }
+
+    const fn ansi_static(open: &'static str) -> Self {
+        Style {
+            open: Cow::Borrowed(open),
+            close: Cow::Borrowed(RESET),
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::MATCH
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Returns the text with each region wrapped in the markers of the style that `style` picks for
+/// it, e.g. by `pattern_id` or `group` for the spans of `highlight_all`.
+///
+/// The spans are code point ranges that must be sorted and must not overlap, as `highlight`
+/// returns them; a span that starts before the end of the previous one is skipped.
+///
+/// ```
+/// use py_regex::{PyRegex, Style, render};
+///
+/// let spans = PyRegex::new(r"(\w+)=(\d+)")?.highlight("a=1")?;
+/// let styled = render("a=1", &spans, |span| match span.group {
+///     1 => Style::BLUE,
+///     _ => Style::new("<", ">"),
+/// });
+/// assert_eq!(styled, "\x1b[1;34ma\x1b[0m<=><1>");
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+pub fn render(text: &str, spans: &[Span], mut style: impl FnMut(&Span) -> Style) -> String {
+    let offsets = ByteOffsets::new(text);
+    let len = text.chars().count();
+    let mut out = String::with_capacity(text.len());
+    let mut written = 0;
+    for span in spans {
+        let range = span.range.start.min(len)..span.range.end.min(len);
+        if range.is_empty() || offsets.get(range.start) < written {
+            continue;
+        }
+        let (start, end) = (offsets.get(range.start), offsets.get(range.end));
+        let style = style(span);
+        out.push_str(&text[written..start]);
+        out.push_str(&style.open);
+        out.push_str(&text[start..end]);
+        out.push_str(&style.close);
+        written = end;
+    }
+    out.push_str(&text[written..]);
+    out
+}
+
+/// Returns the regions matched by any of the patterns, as sorted, non-overlapping spans.
+///
+/// Where matches of different patterns overlap, the earlier pattern wins. Within a match, each
+/// position belongs to the innermost group covering it. Adjacent regions with the same pattern
+/// and group are merged, and empty matches are ignored.
+pub fn highlight_all<T: PyText + ?Sized>(patterns: &[PyRegex], text: &T) -> PyResult<Vec<Span>> {
+    let labels = Python::with_gil(|py| {
+        let text = text.to_py_str(py);
+        let mut labels = vec![None; text.len()?];
+        // Paint from the lowest priority to the highest, so that later writes win: the last
+        // pattern first, and within a match the widest groups first.
+        for (pattern_id, re) in patterns.iter().enumerate().rev() {
+            let matches =
+                re.as_py()
+                    .bind(py)
+                    .call_method("finditer", (&text,), re.kwargs(py).as_ref())?;
+            for m in matches.try_iter()? {
+                let mut groups = m?
+                    .getattr("regs")?
+                    .extract::<Vec<(isize, isize)>>()?
+                    .into_iter()
+                    .enumerate()
+                    .filter(|&(_, (start, end))| start >= 0 && end > start)
+                    .map(|(group, (start, end))| (group as u16, start as usize..end as usize))
+                    .collect::<Vec<_>>();
+                groups.sort_by_key(|(group, range)| (std::cmp::Reverse(range.len()), *group));
+                for (group, range) in groups {
+                    labels[range].fill(Some((pattern_id, group)));
+                }
+            }
+        }
+        Ok::<_, PyErr>(labels)
+    })?;
+
+    let mut spans: Vec<Span> = Vec::new();
+    for (position, label) in labels.into_iter().enumerate() {
+        let Some((pattern_id, group)) = label else {
+            continue;
+        };
+        match spans.last_mut() {
+            Some(last)
+                if last.range.end == position
+                    && last.pattern_id == pattern_id
+                    && last.group == group =>
+            {
+                last.range.end += 1;
+            }
+            _ => spans.push(Span {
+                range: position..position + 1,
+                pattern_id,
+                group,
+            }),
+        }
+    }
+    Ok(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let span = |range, pattern_id, group| Span {
+            range,
+            pattern_id,
+            group,
+        };
+        let re = PyRegex::new(r"(\w+)=(\d+)")?;
+        assert_eq!(
+            re.highlight("é a=1")?,
+            [span(2..3, 0, 1), span(3..4, 0, 0), span(4..5, 0, 2)]
+        );
+
+        let patterns = [PyRegex::new(r"\d+")?, PyRegex::new(r"x\d+y")?];
+        assert_eq!(
+            highlight_all(&patterns, "x12y 7")?,
+            [
+                span(0..1, 1, 0),
+                span(1..3, 0, 0),
+                span(3..4, 1, 0),
+                span(5..6, 0, 0)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"é\d|x*")?;
+        assert_eq!(
+            re.render("aé1 é2", &Style::default())?,
+            "a\x1b[1;31mé1\x1b[0m \x1b[1;31mé2\x1b[0m"
+        );
+        assert_eq!(re.render("abc", &Style::ansi("4"))?, "abc");
+
+        let span = |range| Span {
+            range,
+            pattern_id: 0,
+            group: 0,
+        };
+        let spans = [span(1..3), span(2..4), span(4..9)];
+        assert_eq!(
+            render("abcdef", &spans, |_| Style::new("(", ")")),
+            "a(bc)d(ef)"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+//! Configuration of the embedded Python interpreter.
+//!
+//! Deployments where the interpreter cannot find its standard library or the `regex` package
+//! (containers, virtualenvs, relocated installs) can describe the environment with a `Config`
+//! and apply it once at startup, before any pattern is compiled:
+//!
+//! ```no_run
+//! py_regex::init::Config::new()
+//!     .virtualenv("/opt/app/.venv")
+//!     .sys_path("/opt/app/python")
+//!     .apply()?;
+//! # Ok::<(), pyo3::PyErr>(())
+//! ```
+
+use crate::backend;
+use pyo3::exceptions::{PyImportError, PyRuntimeError, PyValueError};
+#[cfg(not(feature = "pypy"))]
+use pyo3::ffi;
+use pyo3::prelude::*;
+#[cfg(not(feature = "pypy"))]
+use std::ffi::{CStr, CString};
+#[cfg(not(feature = "pypy"))]
+use std::mem::MaybeUninit;
+use std::path::{Path, PathBuf};
+
+/// Settings applied to the embedded interpreter by `Config::apply`.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    python_home: Option<PathBuf>,
+    virtualenv: Option<PathBuf>,
+    sys_path: Vec<PathBuf>,
+}
+
+impl Config {
+    /// Creates an empty configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the Python home (as `PYTHONHOME` would), i.e. the prefix holding the standard library.
+    /// It can only take effect if the interpreter has not been initialized yet, and is not
+    /// supported with the `pypy` feature. `apply` returns a `ValueError` if it is not a directory.
+    pub fn python_home(mut self, path: impl Into<PathBuf>) -> Self {
+        self.python_home = Some(path.into());
+        self
+    }
+
+    /// Makes the packages of the given virtualenv importable.
+    pub fn virtualenv(mut self, path: impl Into<PathBuf>) -> Self {
+        self.virtualenv = Some(path.into());
+        self
+    }
+
+    /// Appends an entry to `sys.path`.
+    pub fn sys_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sys_path.push(path.into());
+        self
+    }
+
+    /// Initializes the interpreter if needed, applies the configuration and verifies that the
+    /// `regex` package can be imported. Call it at startup, before any other use of the crate.
+    pub fn apply(self) -> PyResult<()> {
+        if let Some(home) = &self.python_home {
+            initialize_with_home(home)?;
+        }
+        Python::with_gil(|py| {
+            let sys_path = py.import("sys")?.getattr("path")?;
+            for path in &self.sys_path {
+                sys_path.call_method1("append", (path,))?;
+            }
+            if let Some(venv) = &self.virtualenv {
+                let site_packages = site_packages(py, venv)?;
+                py.import("site")?
+                    .call_method1("addsitedir", (site_packages,))?;
+            }
+            Ok::<_, PyErr>(())
+        })?;
+        verify()
+    }
+}
+
+/// Checks that the embedded interpreter can import the `regex` package (or `re`, if the fallback
+/// is enabled), turning an import failure into an error that explains where Python looked.
+pub fn verify() -> PyResult<()> {
+    Python::with_gil(|py| match backend::module(py) {
+        Ok(_) => Ok(()),
+        Err(err) if err.is_instance_of::<PyImportError>(py) => {
+            let sys = py.import("sys")?;
+            Err(PyImportError::new_err(format!(
+                "the Python `regex` package is not importable by the embedded interpreter \
+                 (Python {}, prefix {}, sys.path {}): install it with `pip install regex` \
+                 or point `py_regex::init::Config` at the environment that has it",
+                sys.getattr("version")?.str()?,
+                sys.getattr("prefix")?.str()?,
+                sys.getattr("path")?.repr()?,
+            )))
+        }
+        Err(err) => Err(err),
+    })
+}
+
+/// Returns the `site-packages` directory of a virtualenv for the running Python version.
+fn site_packages(py: Python, venv: &Path) -> PyResult<PathBuf> {
+    let version = py.import("sys")?.getattr("version_info")?;
+    let major = version.getattr("major")?.extract::<u8>()?;
+    let minor = version.getattr("minor")?.extract::<u8>()?;
+    let path = if cfg!(windows) {
+        venv.join("Lib").join("site-packages")
+    } else {
+        let implementation = if cfg!(feature = "pypy") {
+            "pypy"
+        } else {
+            "python"
+        };
+        venv.join("lib")
+            .join(format!("{implementation}{major}.{minor}"))
+            .join("site-packages")
+    };
+    if !path.is_dir() {
+        return Err(PyValueError::new_err(format!(
+            "virtualenv {} has no site-packages for Python {major}.{minor} (expected {})",
+            venv.display(),
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+/// PyPy cannot be embedded, so its interpreter is always running already.
+#[cfg(feature = "pypy")]
+fn initialize_with_home(_home: &Path) -> PyResult<()> {
+    Err(PyRuntimeError::new_err(
+        "python_home is not supported on PyPy, whose interpreter is started by the host process",
+    ))
+}
+
+/// Initializes the interpreter with the given home, the same way `prepare_freethreaded_python`
+/// would otherwise, and releases the GIL afterwards.
+#[cfg(not(feature = "pypy"))]
+fn initialize_with_home(home: &Path) -> PyResult<()> {
+    // Python aborts the process instead of returning an error when the home is missing.
+    if !home.is_dir() {
+        return Err(PyValueError::new_err(format!(
+            "python_home {} is not a directory",
+            home.display()
+        )));
+    }
+    if unsafe { ffi::Py_IsInitialized() } != 0 {
+        return Err(PyRuntimeError::new_err(
+            "the Python interpreter is already initialized; python_home must be set before first use",
+        ));
+    }
+    let home = home
+        .to_str()
+        .and_then(|home| CString::new(home).ok())
+        .ok_or_else(|| {
+            PyValueError::new_err("python_home must be valid UTF-8 without NUL bytes")
+        })?;
+    // SAFETY: the config is initialized by `PyConfig_InitPythonConfig` before use and cleared
+    // before returning; the interpreter is not running yet, as checked above.
+    unsafe {
+        let mut config = MaybeUninit::<ffi::PyConfig>::uninit();
+        ffi::PyConfig_InitPythonConfig(config.as_mut_ptr());
+        let mut config = config.assume_init();
+        config.install_signal_handlers = 0;
+        let mut status = ffi::PyConfig_SetBytesString(&mut config, &mut config.home, home.as_ptr());
+        if ffi::PyStatus_Exception(status) == 0 {
+            status = ffi::Py_InitializeFromConfig(&config);
+        }
+        ffi::PyConfig_Clear(&mut config);
+        if ffi::PyStatus_Exception(status) != 0 {
+            let message = if status.err_msg.is_null() {
+                "unknown error".into()
+            } else {
+                CStr::from_ptr(status.err_msg).to_string_lossy()
+            };
+            return Err(PyRuntimeError::new_err(format!(
+                "failed to initialize the Python interpreter: {message}"
+            )));
+        }
+        ffi::PyEval_SaveThread();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        verify()?;
+        Config::new().sys_path(std::env::temp_dir()).apply()?;
+        let missing = std::env::temp_dir().join("py-regex-missing-venv");
+        let err = Config::new().virtualenv(&missing).apply().unwrap_err();
+        assert!(Python::with_gil(|py| err.is_instance_of::<PyValueError>(py)));
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "pypy"))]
+    #[test]
+    fn test_invalid_python_home() {
+        pyo3::prepare_freethreaded_python();
+
+        let missing = std::env::temp_dir().join("py-regex-missing-python-home");
+        let err = Config::new().python_home(&missing).apply().unwrap_err();
+        assert!(Python::with_gil(|py| err.is_instance_of::<PyValueError>(py)));
+        assert!(err.to_string().contains("is not a directory"));
+
+        let err = Config::new()
+            .python_home(std::env::temp_dir())
+            .apply()
+            .unwrap_err();
+        assert!(Python::with_gil(
+            |py| err.is_instance_of::<PyRuntimeError>(py)
+        ));
+        assert!(err.to_string().contains("already initialized"));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::match_data::MatchData;
+use std::io::{self, BufWriter, Write};
+
+/// Writes matches as JSON Lines, one record per match, e.g. to feed extraction results into a
+/// log pipeline.
+///
+/// Each record is the `MatchData` JSON with the id of the source it was found in, such as a
+/// file name: `{"source": ..., "match": ..., "span": [start, end], "groups": [...], "named": {...}}`.
+/// Records go through a buffer of bounded size, which is flushed when full and by `flush`; call
+/// `flush` or `into_inner` at the end, since errors are lost when the writer is just dropped.
+///
+/// ```
+/// use py_regex::{MatchWriter, PyRegex};
+///
+/// let mut writer = MatchWriter::new(Vec::new());
+/// for m in PyRegex::new(r"(?P<user>\w+)@")?.find_iter("alice@example.org")? {
+///     writer.write("inbox.txt", &m.to_data()?)?;
+/// }
+/// let out = String::from_utf8(writer.into_inner()?).unwrap();
+/// assert!(out.starts_with(r#"{"source":"inbox.txt","match":"alice@","#));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct MatchWriter<W: Write> {
+    out: BufWriter<W>,
+}
+
+#[derive(serde::Serialize)]
+struct Record<'a> {
+    source: &'a str,
+    #[serde(flatten)]
+    data: &'a MatchData,
+}
+
+impl<W: Write> MatchWriter<W> {
+    /// Creates a writer with the default buffer size of `BufWriter`.
+    pub fn new(out: W) -> Self {
+        MatchWriter {
+            out: BufWriter::new(out),
+        }
+    }
+
+    /// Creates a writer that buffers at most `capacity` bytes before writing to `out`.
+    pub fn with_capacity(capacity: usize, out: W) -> Self {
+        MatchWriter {
+            out: BufWriter::with_capacity(capacity, out),
+        }
+    }
+
+    /// Writes one record for the match.
+    pub fn write(&mut self, source: &str, data: &MatchData) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, &Record { source, data })?;
+        self.out.write_all(b"\n")
+    }
+
+    /// Writes one record for each of the matches.
+    pub fn write_all<'a>(
+        &mut self,
+        source: &str,
+        matches: impl IntoIterator<Item = &'a MatchData>,
+    ) -> io::Result<()> {
+        matches
+            .into_iter()
+            .try_for_each(|data| self.write(source, data))
+    }
+
+    /// Writes out the buffered records.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    /// Writes out the buffered records and returns the underlying writer.
+    pub fn into_inner(self) -> io::Result<W> {
+        self.out
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_match_writer() -> io::Result<()> {
+        let data = MatchData::new(vec![Some(("k=é".to_string(), 0..3)), None], HashMap::new());
+        let mut writer = MatchWriter::with_capacity(16, Vec::new());
+        writer.write_all("a.log", [&data, &data])?;
+        let out = String::from_utf8(writer.into_inner()?).unwrap();
+        let record = r#"{"source":"a.log","match":"k=é","span":[0,3],"groups":[null],"named":{}}"#;
+        assert_eq!(out, format!("{record}\n{record}\n"));
+
+        Ok(())
+    }
+}
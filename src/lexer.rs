@@ -0,0 +1,220 @@
+use crate::flags::Flags;
+use crate::haystack::PyText;
+use crate::helpers::helpers;
+use crate::python::{PyRegex, PyRegexMatch};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::ops::Range;
+
+/// A token produced by a `Lexer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// The name of the token pattern that matched.
+    pub name: String,
+    /// The code point span of the token in the input.
+    pub span: Range<usize>,
+    /// The matched text.
+    pub text: String,
+}
+
+/// A scanner built from an ordered list of named token patterns, compiled into a single
+/// alternation of named groups. At each position the first pattern that matches wins.
+///
+/// ```
+/// use py_regex::Lexer;
+///
+/// let lexer = Lexer::new([("number", r"\d+"), ("op", r"[-+*/]"), ("space", r"\s+")])?;
+/// let names = lexer
+///     .tokenize("1 + 22")?
+///     .into_iter()
+///     .map(|token| token.name)
+///     .collect::<Vec<_>>();
+/// assert_eq!(names, ["number", "space", "op", "space", "number"]);
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lexer {
+    re: PyRegex,
+}
+
+impl Lexer {
+    /// Compiles the `(token_name, pattern)` pairs, in priority order. Token names must be valid
+    /// group names, and the patterns should not contain named groups of their own.
+    pub fn new<I, N, P>(tokens: I) -> PyResult<Self>
+    where
+        I: IntoIterator<Item = (N, P)>,
+        N: AsRef<str>,
+        P: AsRef<str>,
+    {
+        Self::with_flags(tokens, Flags::NONE)
+    }
+
+    /// Compiles the `(token_name, pattern)` pairs with the given flags.
+    pub fn with_flags<I, N, P>(tokens: I, flags: Flags) -> PyResult<Self>
+    where
+        I: IntoIterator<Item = (N, P)>,
+        N: AsRef<str>,
+        P: AsRef<str>,
+    {
+        let alternatives = tokens
+            .into_iter()
+            .map(|(name, pattern)| format!("(?P<{}>{})", name.as_ref(), pattern.as_ref()))
+            .collect::<Vec<_>>();
+        if alternatives.is_empty() {
+            return Err(PyValueError::new_err("a lexer needs at least one token"));
+        }
+        Ok(Lexer {
+            re: PyRegex::with_flags(&alternatives.join("|"), flags)?,
+        })
+    }
+
+    /// Returns the combined pattern.
+    pub fn regex(&self) -> &PyRegex {
+        &self.re
+    }
+
+    /// Splits the whole text into tokens. Fails with a `ValueError` at the first position where
+    /// no token matches.
+    ///
+    /// Every token is non-empty: a pattern that matches the empty string, such as `\s*`, never
+    /// produces a token of its own, and the scan goes on with the next pattern that consumes text.
+    pub fn tokenize<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<Token>> {
+        Python::with_gil(|py| {
+            let text = text.to_py_str(py);
+            let matches = self.re.as_py().bind(py).call_method(
+                "finditer",
+                (&text,),
+                self.re.kwargs(py).as_ref(),
+            )?;
+            let mut tokens = Vec::new();
+            let mut position = 0;
+            for m in matches.try_iter()? {
+                let m = m?;
+                let (start, end) = m.call_method0("span")?.extract::<(usize, usize)>()?;
+                if start == end {
+                    continue;
+                }
+                if start != position {
+                    return Err(unexpected(position));
+                }
+                tokens.push(Token {
+                    name: m.getattr("lastgroup")?.extract()?,
+                    span: start..end,
+                    text: m.call_method1("group", (0,))?.extract()?,
+                });
+                position = end;
+            }
+            if position != text.len()? {
+                return Err(unexpected(position));
+            }
+            Ok(tokens)
+        })
+    }
+}
+
+/// Searches the text from the code point position `pos` with every pattern and returns the
+/// index of the pattern whose match starts first and, among those, is longest, along with the
+/// match. Ties go to the earlier pattern, as in the "longest match" rule of lexer generators.
+///
+/// All patterns are searched in a single call into Python.
+///
+/// ```
+/// use py_regex::{PyRegex, find_longest};
+///
+/// let patterns = [PyRegex::new(r"=")?, PyRegex::new(r"==")?, PyRegex::new(r"\w+")?];
+/// let (index, m) = find_longest(&patterns, "a == b", 1)?.unwrap();
+/// assert_eq!((index, m.span(0)?), (1, Some(2..4)));
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+pub fn find_longest<T: PyText + ?Sized>(
+    patterns: &[PyRegex],
+    text: &T,
+    pos: usize,
+) -> PyResult<Option<(usize, PyRegexMatch)>> {
+    Python::with_gil(|py| {
+        let text = text.to_py_str(py);
+        let compiled = patterns.iter().map(PyRegex::as_py).collect::<Vec<_>>();
+        let kwargs = patterns.iter().map(|re| re.kwargs(py)).collect::<Vec<_>>();
+        let best = helpers(py)?
+            .call_method1("find_longest", (compiled, text, pos, kwargs))?
+            .extract::<Option<(usize, Py<PyAny>)>>()?;
+        Ok(best.map(|(index, m)| (index, PyRegexMatch::new(m))))
+    })
+}
+
+fn unexpected(position: usize) -> PyErr {
+    PyValueError::new_err(format!("no token matches at position {position}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lexer() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let lexer = Lexer::new([
+            ("keyword", r"\b(?:let|in)\b"),
+            ("name", r"\w+"),
+            ("op", r"="),
+            ("space", r"\s+"),
+        ])?;
+        let tokens = lexer.tokenize("let x = é")?;
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| token.name.as_str())
+                .collect::<Vec<_>>(),
+            ["keyword", "space", "name", "space", "op", "space", "name"]
+        );
+        assert_eq!(tokens[6].span, 8..9);
+        assert_eq!(tokens[6].text, "é");
+        assert!(lexer.tokenize("x ? y").is_err());
+        assert!(Lexer::new(Vec::<(&str, &str)>::new()).is_err());
+
+        let names = |lexer: &Lexer, text| -> PyResult<Vec<String>> {
+            Ok(lexer
+                .tokenize(text)?
+                .into_iter()
+                .map(|token| token.name)
+                .collect())
+        };
+        let optional_space = Lexer::new([("number", r"\d+"), ("space", r"\s*")])?;
+        assert_eq!(
+            names(&optional_space, "1 2")?,
+            ["number", "space", "number"]
+        );
+        assert_eq!(names(&optional_space, "12")?, ["number"]);
+        assert!(names(&optional_space, "")?.is_empty());
+        let err = optional_space.tokenize("1+2").unwrap_err();
+        assert!(err.to_string().contains("position 1"));
+        // An empty match of an earlier pattern does not hide a later one.
+        let shadowing = Lexer::new([("x", r"x*"), ("y", r"y")])?;
+        assert_eq!(names(&shadowing, "yxxy")?, ["y", "x", "y"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_longest() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let patterns = [
+            PyRegex::new(r"\d")?,
+            PyRegex::new(r"é\d+")?,
+            PyRegex::new(r"é\d\d")?,
+        ];
+        let longest = |text, pos| -> PyResult<Option<(usize, Option<Range<usize>>)>> {
+            find_longest(&patterns, text, pos)?
+                .map(|(index, m)| Ok((index, m.span(0)?)))
+                .transpose()
+        };
+        assert_eq!(longest("xé12", 0)?, Some((1, Some(1..4))));
+        assert_eq!(longest("xé12", 2)?, Some((0, Some(2..3))));
+        assert_eq!(longest("x", 0)?, None);
+        assert!(find_longest(&[], "x", 0)?.is_none());
+
+        Ok(())
+    }
+}
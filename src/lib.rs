@@ -1,216 +1,172 @@
+#[cfg(feature = "python")]
 pub extern crate pyo3;
-use pyo3::PyResult;
-use pyo3::prelude::*;
-use pyo3::types::{PyAny, PyDict, PyIterator, PyModule};
-use std::collections::HashMap;
-
-/// A wrapper for a compiled regular expression from the Python `regex` library.
-#[derive(Debug)]
-pub struct PyRegex {
-    compiled: Py<PyAny>,
-}
-impl PyRegex {
-    /// Creates a new regular expression by compiling the pattern via Python's `regex.compile`.
-    pub fn new(pattern: &str) -> PyResult<Self> {
-        Python::with_gil(|py| {
-            Ok(PyRegex {
-                compiled: PyModule::import(py, "regex")?
-                    .call_method("compile", (pattern,), None)?
-                    .into(),
-            })
-        })
-    }
-
-    /// Constructs kwargs with `concurrent=True`.
-    fn kwargs(py: Python) -> Option<Bound<PyDict>> {
-        let kwargs = PyDict::new(py);
-        kwargs.set_item("concurrent", true).ok()?;
-        Some(kwargs)
-    }
-
-    /// Performs a search for the first match and returns a `PyRegexMatch` object.
-    pub fn search_match(&self, text: &str) -> PyResult<Option<PyRegexMatch>> {
-        Python::with_gil(|py| {
-            let result =
-                self.compiled
-                    .call_method(py, "search", (text,), Self::kwargs(py).as_ref())?;
-
-            Ok(if result.is_none(py) {
-                None
-            } else {
-                Some(PyRegexMatch { inner: result })
-            })
-        })
-    }
-
-    /// Returns a list of `PyRegexMatch` objects from `finditer()`.
-    pub fn find_iter(&self, text: &str) -> PyResult<Vec<PyRegexMatch>> {
-        Python::with_gil(|py| {
-            let mut matches = Vec::new();
-            let binding =
-                self.compiled
-                    .call_method(py, "finditer", (text,), Self::kwargs(py).as_ref())?;
-            let iter = binding.downcast_bound::<PyIterator>(py)?;
-            for item in iter {
-                let match_obj = item?;
-                matches.push(PyRegexMatch {
-                    inner: match_obj.into(),
-                });
-            }
-            Ok(matches)
-        })
-    }
-
-    // Other methods remain unchanged.
-    pub fn is_match(&self, text: &str) -> PyResult<bool> {
-        Python::with_gil(|py| {
-            Ok(!self
-                .compiled
-                .call_method(py, "search", (text,), Self::kwargs(py).as_ref())?
-                .is_none(py))
-        })
-    }
-
-    pub fn find_all(&self, text: &str) -> PyResult<Vec<String>> {
-        Python::with_gil(|py| {
-            self.compiled
-                .call_method(py, "findall", (text,), Self::kwargs(py).as_ref())?
-                .extract::<Vec<String>>(py)
-        })
-    }
-
-    pub fn replace(&self, text: &str, replacement: &str) -> PyResult<String> {
-        Python::with_gil(|py| {
-            self.compiled
-                .call_method(py, "sub", (replacement, text), Self::kwargs(py).as_ref())?
-                .extract::<String>(py)
-        })
-    }
-
-    pub fn split(&self, text: &str) -> PyResult<Vec<String>> {
-        Python::with_gil(|py| {
-            self.compiled
-                .call_method(py, "split", (text,), Self::kwargs(py).as_ref())?
-                .extract::<Vec<String>>(py)
-        })
-    }
-
-    /// Escapes a string.
-    pub fn escape(str: &str, special_only: bool, literal_spaces: bool) -> PyResult<String> {
-        Python::with_gil(|py| {
-            let kwargs = PyDict::new(py);
-            kwargs.set_item("special_only", special_only)?;
-            kwargs.set_item("literal_spaces", literal_spaces)?;
-            PyModule::import(py, "regex")?
-                .call_method("escape", (str,), Some::<Bound<PyDict>>(kwargs).as_ref())?
-                .extract::<String>()
-        })
-    }
-}
-
-/// A wrapper for the match object from the Python `regex` module.
-pub struct PyRegexMatch {
-    inner: Py<PyAny>,
-}
-
-impl PyRegexMatch {
-    /// Returns the match for the specified group.
-    /// For example, `group(0)` is the entire match, `group(1)` is the first subgroup, etc.
-    pub fn group(&self, group: u16) -> PyResult<Option<String>> {
-        Python::with_gil(|py| {
-            self.inner
-                .call_method1(py, "group", (group as usize,))?
-                .extract::<Option<String>>(py)
-        })
-    }
-
-    /// Returns all captured groups as a vector.
-    /// Analogous to Python's `groups()` method, which returns a tuple of all subgroups (starting from 1).
-    pub fn groups(&self) -> PyResult<Vec<Option<String>>> {
-        Python::with_gil(|py| {
-            self.inner
-                .call_method1(py, "groups", ())?
-                .extract::<Vec<Option<String>>>(py)
-        })
-    }
-
-    /// Returns the named groups dictionary (`groupdict()`) as a `HashMap`.
-    pub fn groupdict(&self) -> PyResult<HashMap<String, Option<String>>> {
-        Python::with_gil(|py| {
-            self.inner
-                .call_method1(py, "groupdict", ())?
-                .extract::<HashMap<String, Option<String>>>(py)
-        })
-    }
-
-    /// Returns the start position of the match for the specified group.
-    pub fn start(&self, group: u16) -> PyResult<isize> {
-        Python::with_gil(|py| {
-            self.inner
-                .call_method1(py, "start", (group as usize,))?
-                .extract::<isize>(py)
-        })
-    }
-
-    /// Returns the end position of the match for the specified group.
-    pub fn end(&self, group: u16) -> PyResult<isize> {
-        Python::with_gil(|py| {
-            self.inner
-                .call_method1(
-                    py,
-                    "end",
-                    (group as usize,), /* Option<&pyo3::Bound<'_, PyDict>> */
-                )?
-                .extract::<isize>(py)
-        })
-    }
+// Lets the tests use macros that expand to `::py_regex::...` paths.
+#[cfg(all(test, feature = "macros"))]
+extern crate self as py_regex;
+
+mod analysis;
+mod engine;
+mod flags;
+mod group;
+mod lines;
+mod lint;
+mod match_data;
+mod offsets;
+
+#[cfg(feature = "python")]
+mod aggregate;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "tokio")]
+pub mod r#async;
+#[cfg(feature = "python")]
+mod backend;
+#[cfg(feature = "python")]
+mod breaker;
+#[cfg(feature = "python")]
+mod builder;
+#[cfg(feature = "bstr")]
+mod bytes;
+#[cfg(feature = "python")]
+mod cancel;
+#[cfg(feature = "compat")]
+pub mod compat;
+#[cfg(feature = "python")]
+mod compose;
+#[cfg(feature = "python")]
+mod context;
+#[cfg(feature = "python")]
+mod examples;
+#[cfg(feature = "fallback-fancy-regex")]
+pub mod fancy;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+mod filter;
+#[cfg(feature = "python")]
+mod from_captures;
+#[cfg(feature = "python")]
+mod haystack;
+#[cfg(feature = "python")]
+mod helpers;
+#[cfg(feature = "python")]
+mod highlight;
+#[cfg(feature = "python")]
+pub mod init;
+#[cfg(feature = "json")]
+mod jsonl;
+#[cfg(feature = "python")]
+mod lexer;
+#[cfg(feature = "python")]
+mod macros;
+#[cfg(feature = "python")]
+mod memo;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "python")]
+pub mod patterns;
+#[cfg(feature = "python")]
+mod pipeline;
+#[cfg(feature = "python")]
+mod preview;
+#[cfg(feature = "python")]
+mod properties;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "python")]
+mod records;
+#[cfg(feature = "python")]
+mod redact;
+#[cfg(feature = "python")]
+mod runtime;
+#[cfg(feature = "python")]
+mod stream;
+#[cfg(feature = "python")]
+mod template;
+#[cfg(feature = "python")]
+pub mod testutil;
+#[cfg(feature = "python")]
+mod trace;
+#[cfg(feature = "python")]
+mod validate;
+#[cfg(feature = "python")]
+pub mod worker;
+
+#[cfg(feature = "python")]
+pub use aggregate::MatchStats;
+pub use analysis::{Construct, Finding, PatternReport, analyze};
+#[cfg(feature = "python")]
+pub use backend::{
+    Backend, BackendInfo, Feature, UnsupportedFeature, backend, backend_info, set_re_fallback,
+};
+#[cfg(feature = "python")]
+pub use breaker::{CircuitBreaker, CircuitOpen};
+#[cfg(feature = "python")]
+pub use builder::PyRegexBuilder;
+#[cfg(feature = "python")]
+pub use cancel::{CancellationToken, Cancelled};
+#[cfg(feature = "python")]
+pub use compose::PatternBuilder;
+#[cfg(feature = "python")]
+pub use context::ContextMatch;
+pub use engine::Engine;
+#[cfg(feature = "fallback-fancy-regex")]
+pub use engine::FancyEngine;
+#[cfg(feature = "python")]
+pub use engine::PythonEngine;
+#[cfg(feature = "python")]
+pub use filter::MatchFilter;
+pub use flags::{Flags, Version};
+#[cfg(feature = "python")]
+pub use from_captures::FromCaptures;
+pub use group::Group;
+#[cfg(feature = "python")]
+pub use haystack::{Haystack, PyText};
+#[cfg(feature = "python")]
+pub use highlight::{Span, Style, highlight_all, render};
+#[cfg(feature = "json")]
+pub use jsonl::MatchWriter;
+#[cfg(feature = "python")]
+pub use lexer::{Lexer, Token, find_longest};
+pub use lines::LineIndex;
+pub use lint::{LintKind, LintWarning, lint};
+pub use match_data::MatchData;
+#[cfg(feature = "python")]
+pub use memo::Memoized;
+#[cfg(feature = "bstr")]
+pub use offsets::bstr_char_to_byte_offset;
+pub use offsets::char_to_byte_offset;
+#[cfg(feature = "python")]
+pub use pipeline::Pipeline;
+#[cfg(feature = "python")]
+pub use preview::Substitution;
+#[cfg(feature = "python")]
+pub use properties::{property_ranges, supports_property};
+#[cfg(feature = "macros")]
+pub use py_regex_macros::{FromCaptures, py_regex};
+#[cfg(feature = "python")]
+pub use pyo3::PyResult;
+#[cfg(feature = "python")]
+pub use python::*;
+#[cfg(feature = "python")]
+pub use records::{RecordSplitter, Records};
+#[cfg(feature = "python")]
+pub use redact::{Policy, Redacted, Redaction, Redactor};
+#[cfg(feature = "python")]
+pub use runtime::{Runtime, shutdown};
+#[cfg(feature = "python")]
+pub use stream::{IncrementalMatcher, SplitStream, StreamMatch};
+#[cfg(feature = "python")]
+pub use template::TemplateError;
+#[cfg(feature = "python")]
+pub use validate::{FieldError, FieldErrorKind, Validator};
+
+/// Support code for the macros; not part of the public API.
+#[cfg(feature = "python")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::from_captures::{missing_group, parse_group};
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_escape() -> PyResult<()> {
-        // Initialize Python for multithreaded usage.
-        pyo3::prepare_freethreaded_python();
-
-        assert_eq!(PyRegex::escape("[]", false, false)?, "\\[\\]");
-
-        Ok(())
-    }
-    #[test]
-    fn test_pyregex_match_methods() -> PyResult<()> {
-        // Initialize Python for multithreaded usage.
-        pyo3::prepare_freethreaded_python();
-
-        // Use a pattern with a named group and multiple subgroups.
-        let pattern = r"(?P<word>\w+)-(\d+)";
-        let text = "Test-123";
-        let re = PyRegex::new(pattern)?;
-
-        if let Some(m) = re.search_match(text)? {
-            // Check the full match via group(0)
-            assert_eq!(m.group(0)?, Some("Test-123".to_string()));
-
-            // First subgroup (without a name)
-            assert_eq!(m.group(1)?, Some("Test".to_string()));
-
-            // Second subgroup (the number)
-            assert_eq!(m.group(2)?, Some("123".to_string()));
-
-            // Get the named groups dictionary
-            let gd = m.groupdict()?;
-            assert_eq!(gd.get("word").cloned(), Some(Some("Test".to_string())));
-
-            // Get the match span for group 0
-            let start = m.start(0)?;
-            let end = m.end(0)?;
-            println!("Match span for group 0: {}..{}", start, end);
-        } else {
-            panic!("No match found");
-        }
-
-        Ok(())
-    }
-}
+#[cfg(all(feature = "fallback-fancy-regex", not(feature = "python")))]
+pub use fancy::{PyRegex, PyRegexMatch, PyResult};
@@ -0,0 +1,88 @@
+use std::ops::Range;
+
+/// The lines of a text, built once so that code point positions such as match spans can be
+/// turned into line and column numbers with a binary search instead of a scan per match.
+///
+/// Lines are separated by `\n`, and a `\r` before it is not part of the line. Lines and columns
+/// are 1-based, as in compiler diagnostics, and columns count code points.
+///
+/// ```
+/// use py_regex::LineIndex;
+///
+/// let text = "let a = 1;\nlet é = ;\n";
+/// let index = LineIndex::new(text);
+/// assert_eq!(index.line_col(19), (2, 9));
+/// assert_eq!(index.line_span(2), Some(11..20));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// The code point range of each line, without its terminator.
+    lines: Vec<Range<usize>>,
+}
+
+impl LineIndex {
+    /// Indexes the lines of the text. A final line terminator does not start another line.
+    pub fn new(text: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut pos = 0;
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            pos += 1;
+            if c == '\n' {
+                lines.push(start..pos - 1);
+                start = pos;
+            } else if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+                pos += 1;
+                lines.push(start..pos - 2);
+                start = pos;
+            }
+        }
+        if start < pos || lines.is_empty() {
+            lines.push(start..pos);
+        }
+        LineIndex { lines }
+    }
+
+    /// Returns the number of lines.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the line and column of a code point position. Positions within a line terminator
+    /// or past the end of the text count as columns after the end of their line.
+    pub fn line_col(&self, pos: usize) -> (usize, usize) {
+        let line = self.lines.partition_point(|line| line.start <= pos).max(1);
+        (line, pos - self.lines[line - 1].start + 1)
+    }
+
+    /// Returns the code point range of the line, without its terminator, or `None` if there is
+    /// no such line.
+    pub fn line_span(&self, line: usize) -> Option<Range<usize>> {
+        self.lines.get(line.checked_sub(1)?).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let index = LineIndex::new("ab\r\nc\n\né");
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line_col(0), (1, 1));
+        assert_eq!(index.line_col(2), (1, 3));
+        assert_eq!(index.line_col(4), (2, 1));
+        assert_eq!(index.line_col(6), (3, 1));
+        assert_eq!(index.line_col(7), (4, 1));
+        assert_eq!(index.line_span(1), Some(0..2));
+        assert_eq!(index.line_span(3), Some(6..6));
+        assert_eq!(index.line_span(5), None);
+        assert_eq!(index.line_span(0), None);
+
+        assert_eq!(LineIndex::new("").line_count(), 1);
+        assert_eq!(LineIndex::new("a\n").line_count(), 1);
+    }
+}
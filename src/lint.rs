@@ -0,0 +1,754 @@
+use std::fmt;
+
+/// A construct that can make matching take exponential or polynomial time, found by `lint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LintKind {
+    /// A repetition containing a variable repetition that can also match the text after it,
+    /// such as `(a+)+`, `(\w+\s?)*` or `(.*,)*`. Exponential in the length of the text.
+    NestedQuantifier,
+    /// A repetition of alternatives that can start with the same character, such as `(a|ab)*`
+    /// or `(\w|\d)+`. Exponential in the length of the text.
+    OverlappingAlternation,
+    /// Unbounded repetitions of overlapping single characters with only optional items between
+    /// them, such as `\d+\d*` or `.*\s?.*`. Polynomial in the length of the text.
+    AdjacentQuantifiers,
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintKind::NestedQuantifier => "nested quantifier",
+            LintKind::OverlappingAlternation => "overlapping alternatives in a repetition",
+            LintKind::AdjacentQuantifiers => "adjacent quantifiers over overlapping characters",
+        })
+    }
+}
+
+/// A backtracking risk and the byte offset in the pattern where the affected repetition starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintWarning {
+    /// The kind of risk.
+    pub kind: LintKind,
+    /// The byte offset of the repeated item in the pattern.
+    pub offset: usize,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+/// Checks the pattern for constructs prone to catastrophic backtracking (ReDoS), without
+/// compiling it, e.g. to reject user-supplied rules before they reach a matcher.
+///
+/// The check is a heuristic over the characters each item can match: it ignores case-insensitive
+/// matching and does not validate the pattern, so it can miss risks and report repetitions that
+/// cannot actually match the same text in several ways. Possessive quantifiers and atomic groups
+/// are not reported.
+///
+/// ```
+/// use py_regex::{LintKind, lint};
+///
+/// assert!(lint(r"^[\w.+-]+@\w+\.\w{2,}$").is_empty());
+/// assert_eq!(lint(r"^(\w+\s?)*$")[0].kind, LintKind::NestedQuantifier);
+/// ```
+pub fn lint(pattern: &str) -> Vec<LintWarning> {
+    let mut parser = Parser {
+        pattern,
+        bytes: pattern.as_bytes(),
+        i: 0,
+        verbose: false,
+    };
+    let mut alternatives = parser.alternatives();
+    // Unbalanced `)`.
+    while parser.i < parser.bytes.len() {
+        parser.i += 1;
+        alternatives.extend(parser.alternatives());
+    }
+    let mut linter = Linter::default();
+    for alternative in &alternatives {
+        linter.sequence(alternative, false);
+    }
+    linter.warnings.sort_by_key(|warning| warning.offset);
+    linter.warnings
+}
+
+/// A set of characters: exact for ASCII, with a single bit for everything else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct CharSet {
+    ascii: u128,
+    other: bool,
+}
+
+impl CharSet {
+    const ANY: CharSet = CharSet {
+        ascii: u128::MAX,
+        other: true,
+    };
+    const EMPTY: CharSet = CharSet {
+        ascii: 0,
+        other: false,
+    };
+    const NON_ASCII: CharSet = CharSet {
+        ascii: 0,
+        other: true,
+    };
+
+    fn char(c: char) -> Self {
+        Self::range(c, c)
+    }
+
+    fn range(low: char, high: char) -> Self {
+        let mut ascii = 0;
+        for code in (low as u32)..=(high as u32).min(127) {
+            ascii |= 1 << code;
+        }
+        CharSet {
+            ascii,
+            other: high as u32 > 127,
+        }
+    }
+
+    fn bytes(bytes: &[u8]) -> Self {
+        bytes
+            .iter()
+            .fold(Self::EMPTY, |set, &b| set.union(Self::char(b as char)))
+    }
+
+    fn union(self, other: Self) -> Self {
+        CharSet {
+            ascii: self.ascii | other.ascii,
+            other: self.other || other.other,
+        }
+    }
+
+    fn complement(self) -> Self {
+        CharSet {
+            ascii: !self.ascii,
+            other: true,
+        }
+    }
+
+    fn intersects(self, other: Self) -> bool {
+        self.ascii & other.ascii != 0 || self.other && other.other
+    }
+
+    /// The set of a `\d`, `\w` or `\s` escape or their negations.
+    fn class_escape(c: char) -> Option<Self> {
+        let digits = Self::range('0', '9');
+        let word = digits
+            .union(Self::range('a', 'z'))
+            .union(Self::range('A', 'Z'))
+            .union(Self::char('_'));
+        let space = Self::bytes(b" \t\n\r\x0b\x0c");
+        Some(match c {
+            'd' => digits,
+            'D' => digits.complement(),
+            'w' => word.union(Self::NON_ASCII),
+            'W' => word.complement(),
+            's' => space.union(Self::NON_ASCII),
+            'S' => space.complement(),
+            _ => return None,
+        })
+    }
+}
+
+/// A parsed pattern item, reduced to what the checks need.
+#[derive(Debug)]
+enum Node {
+    /// One character from the set.
+    Char(CharSet),
+    /// The empty string: anchors and inline flags.
+    Empty,
+    /// Text the parser does not describe, such as a backreference.
+    Unknown,
+    /// A lookaround, which matches the empty string.
+    Look(Box<Node>),
+    Group {
+        alternatives: Vec<Vec<Node>>,
+        atomic: bool,
+    },
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+        possessive: bool,
+        /// The byte offset of the repeated item.
+        offset: usize,
+    },
+}
+
+impl Node {
+    fn nullable(&self) -> bool {
+        match self {
+            Node::Char(_) => false,
+            Node::Empty | Node::Unknown | Node::Look(_) => true,
+            Node::Group { alternatives, .. } => alternatives.iter().any(|seq| nullable(seq)),
+            Node::Repeat { node, min, .. } => *min == 0 || node.nullable(),
+        }
+    }
+
+    /// The characters the node can start with.
+    fn first(&self) -> CharSet {
+        match self {
+            Node::Char(set) => *set,
+            Node::Empty | Node::Look(_) => CharSet::EMPTY,
+            Node::Unknown => CharSet::ANY,
+            Node::Group { alternatives, .. } => alternatives
+                .iter()
+                .fold(CharSet::EMPTY, |set, seq| set.union(first(seq))),
+            Node::Repeat { node, max, .. } if *max != Some(0) => node.first(),
+            Node::Repeat { .. } => CharSet::EMPTY,
+        }
+    }
+
+    /// All characters the node can match.
+    fn chars(&self) -> CharSet {
+        match self {
+            Node::Char(set) => *set,
+            Node::Empty | Node::Look(_) => CharSet::EMPTY,
+            Node::Unknown => CharSet::ANY,
+            Node::Group { alternatives, .. } => alternatives
+                .iter()
+                .flatten()
+                .fold(CharSet::EMPTY, |set, node| set.union(node.chars())),
+            Node::Repeat { node, .. } => node.chars(),
+        }
+    }
+
+    /// Returns `(offset, chars)` for an unbounded repetition of a single character that can
+    /// backtrack, such as `\d+`.
+    fn unbounded(&self) -> Option<(usize, CharSet)> {
+        match self {
+            Node::Repeat {
+                node,
+                max: None,
+                possessive: false,
+                offset,
+                ..
+            } if matches!(**node, Node::Char(_) | Node::Unknown) => Some((*offset, node.chars())),
+            _ => None,
+        }
+    }
+}
+
+fn nullable(seq: &[Node]) -> bool {
+    seq.iter().all(Node::nullable)
+}
+
+fn first(seq: &[Node]) -> CharSet {
+    let mut set = CharSet::EMPTY;
+    for node in seq {
+        set = set.union(node.first());
+        if !node.nullable() {
+            break;
+        }
+    }
+    set
+}
+
+struct Parser<'a> {
+    pattern: &'a str,
+    bytes: &'a [u8],
+    i: usize,
+    verbose: bool,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.i).copied()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.pattern.get(self.i..)?.chars().next()?;
+        self.i += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_past(&mut self, delimiter: u8) {
+        self.i = self.bytes[self.i..]
+            .iter()
+            .position(|&b| b == delimiter)
+            .map_or(self.bytes.len(), |position| self.i + position + 1);
+    }
+
+    /// Parses alternatives up to a `)` or the end, without consuming the `)`.
+    fn alternatives(&mut self) -> Vec<Vec<Node>> {
+        let mut alternatives = vec![self.sequence()];
+        while self.peek() == Some(b'|') {
+            self.i += 1;
+            alternatives.push(self.sequence());
+        }
+        alternatives
+    }
+
+    fn sequence(&mut self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        while let Some(b) = self.peek() {
+            if b == b'|' || b == b')' {
+                break;
+            }
+            let offset = self.i;
+            if let Some(node) = self.atom() {
+                nodes.push(self.quantified(node, offset));
+            }
+        }
+        nodes
+    }
+
+    fn quantified(&mut self, node: Node, offset: usize) -> Node {
+        let Some((min, max)) = self.quantifier() else {
+            return node;
+        };
+        let possessive = match self.peek() {
+            Some(b'+') => {
+                self.i += 1;
+                true
+            }
+            Some(b'?') => {
+                self.i += 1;
+                false
+            }
+            _ => false,
+        };
+        Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+            possessive,
+            offset,
+        }
+    }
+
+    fn quantifier(&mut self) -> Option<(u32, Option<u32>)> {
+        let bounds = match self.peek()? {
+            b'*' => (0, None),
+            b'+' => (1, None),
+            b'?' => (0, Some(1)),
+            b'{' => {
+                let length = self.bytes[self.i..].iter().position(|&b| b == b'}')?;
+                let body = std::str::from_utf8(&self.bytes[self.i + 1..self.i + length]).ok()?;
+                let number = |text: &str| text.parse::<u32>().ok();
+                let bounds = match body.split_once(',') {
+                    None => {
+                        let count = number(body)?;
+                        (count, Some(count))
+                    }
+                    Some((min, max)) => {
+                        if !(min.bytes().chain(max.bytes())).all(|b| b.is_ascii_digit()) {
+                            return None;
+                        }
+                        (number(min).unwrap_or(0), number(max))
+                    }
+                };
+                self.i += length;
+                bounds
+            }
+            _ => return None,
+        };
+        self.i += 1;
+        Some(bounds)
+    }
+
+    /// Parses one item, or returns `None` for comments and fuzzy constraints.
+    fn atom(&mut self) -> Option<Node> {
+        match self.peek()? {
+            b' ' | b'\t' | b'\n' | b'\r' | b'\x0b' | b'\x0c' if self.verbose => {
+                self.i += 1;
+                None
+            }
+            b'#' if self.verbose => {
+                self.skip_past(b'\n');
+                None
+            }
+            b'\\' => Some(self.escape()),
+            b'[' => Some(Node::Char(self.class())),
+            b'(' => self.group(),
+            b'.' => {
+                self.i += 1;
+                Some(Node::Char(CharSet::ANY))
+            }
+            b'^' | b'$' => {
+                self.i += 1;
+                Some(Node::Empty)
+            }
+            b'{' if self.bytes[self.i..]
+                .iter()
+                .take_while(|&&b| b != b'}')
+                .any(|b| b"eids".contains(b)) =>
+            {
+                self.skip_past(b'}');
+                None
+            }
+            _ => self.next_char().map(|c| Node::Char(CharSet::char(c))),
+        }
+    }
+
+    fn escape(&mut self) -> Node {
+        self.i += 1;
+        let Some(c) = self.next_char() else {
+            return Node::Char(CharSet::char('\\'));
+        };
+        match c {
+            'b' | 'B' | 'A' | 'Z' | 'z' | 'G' | 'K' | 'm' | 'M' => Node::Empty,
+            '1'..='9' => {
+                let rest = &self.bytes[self.i - 1..];
+                if rest.len() >= 3 && rest[..3].iter().all(|b| (b'0'..=b'7').contains(b)) {
+                    self.i += 2;
+                    return Node::Char(CharSet::ANY);
+                }
+                while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                    self.i += 1;
+                }
+                Node::Unknown
+            }
+            'g' | 'L' if self.peek() == Some(b'<') => {
+                self.skip_past(b'>');
+                Node::Unknown
+            }
+            c => Node::Char(self.escaped_set(c)),
+        }
+    }
+
+    /// The set of an escaped character `c` that matches one character, after the `\` and `c`.
+    fn escaped_set(&mut self, c: char) -> CharSet {
+        if let Some(set) = CharSet::class_escape(c) {
+            return set;
+        }
+        match c {
+            'p' | 'P' | 'N' => {
+                if self.peek() == Some(b'{') {
+                    self.skip_past(b'}');
+                } else {
+                    self.next_char();
+                }
+                CharSet::ANY
+            }
+            'x' | 'u' | 'U' => {
+                let digits = if c == 'x' && self.peek() == Some(b'{') {
+                    self.i += 1;
+                    let start = self.i;
+                    let end = self.bytes[start..]
+                        .iter()
+                        .position(|&b| b == b'}')
+                        .map_or(self.bytes.len(), |position| start + position);
+                    self.i = (end + 1).min(self.bytes.len());
+                    &self.pattern[start..end]
+                } else {
+                    let length = match c {
+                        'x' => 2,
+                        'u' => 4,
+                        _ => 8,
+                    };
+                    let length = self.bytes[self.i..]
+                        .iter()
+                        .take(length)
+                        .take_while(|b| b.is_ascii_hexdigit())
+                        .count();
+                    self.i += length;
+                    &self.pattern[self.i - length..self.i]
+                };
+                u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map_or(CharSet::ANY, CharSet::char)
+            }
+            _ => CharSet::char(escaped_char(c)),
+        }
+    }
+
+    /// Parses a character class, approximating POSIX classes and set operations.
+    fn class(&mut self) -> CharSet {
+        self.i += 1;
+        let negated = self.peek() == Some(b'^');
+        if negated {
+            self.i += 1;
+        }
+        let mut set = CharSet::EMPTY;
+        let mut start = true;
+        while let Some(b) = self.peek() {
+            if b == b']' && !start {
+                self.i += 1;
+                break;
+            }
+            start = false;
+            let low = match b {
+                b'[' if self.bytes[self.i..].starts_with(b"[:") => {
+                    self.skip_past(b']');
+                    Err(CharSet::ANY)
+                }
+                b'\\' => {
+                    self.i += 1;
+                    match self.next_char() {
+                        Some(c) if "xuUpPNdDwWsS".contains(c) => Err(self.escaped_set(c)),
+                        Some('b') => Ok('\x08'),
+                        Some(c) => Ok(escaped_char(c)),
+                        None => Ok('\\'),
+                    }
+                }
+                _ => Ok(self.next_char().unwrap_or_default()),
+            };
+            let item = match low {
+                Ok(low)
+                    if self.peek() == Some(b'-')
+                        && self.bytes.get(self.i + 1).is_some_and(|&b| b != b']') =>
+                {
+                    self.i += 1;
+                    let high = match self.next_char() {
+                        Some('\\') => self.next_char().map(escaped_char),
+                        high => high,
+                    };
+                    CharSet::range(low, high.unwrap_or(low))
+                }
+                Ok(low) => CharSet::char(low),
+                Err(set) => set,
+            };
+            set = set.union(item);
+        }
+        if negated { set.complement() } else { set }
+    }
+
+    fn group(&mut self) -> Option<Node> {
+        self.i += 1;
+        let rest = &self.bytes[self.i..];
+        let mut atomic = false;
+        let mut look = false;
+        let verbose = self.verbose;
+        if rest.starts_with(b"?#") {
+            self.skip_past(b')');
+            return None;
+        } else if rest.starts_with(b"?P=")
+            || rest.starts_with(b"?P>")
+            || rest.starts_with(b"?&")
+            || rest.starts_with(b"?R)")
+            || is_group_call(rest)
+        {
+            self.skip_past(b')');
+            return Some(Node::Unknown);
+        } else if rest.starts_with(b"?=") || rest.starts_with(b"?!") {
+            self.i += 2;
+            look = true;
+        } else if rest.starts_with(b"?<=") || rest.starts_with(b"?<!") {
+            self.i += 3;
+            look = true;
+        } else if rest.starts_with(b"?P<") || rest.starts_with(b"?<") {
+            self.skip_past(b'>');
+        } else if rest.starts_with(b"?'") {
+            self.i += 2;
+            self.skip_past(b'\'');
+        } else if rest.starts_with(b"?>") {
+            self.i += 2;
+            atomic = true;
+        } else if rest.starts_with(b"?(") {
+            // The condition of a conditional, then its `yes|no` alternatives.
+            self.skip_past(b')');
+        } else if rest.starts_with(b"?|") || rest.starts_with(b"?:") {
+            self.i += 2;
+        } else if rest.starts_with(b"?") {
+            self.i += 1;
+            let flags = self.bytes[self.i..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphabetic() || **b == b'-')
+                .count();
+            let on = self.bytes[self.i..self.i + flags]
+                .split(|&b| b == b'-')
+                .next();
+            if on.is_some_and(|on| on.contains(&b'x')) {
+                self.verbose = true;
+            }
+            self.i += flags;
+            if self.peek() == Some(b')') {
+                // Inline flags apply to the rest of the pattern.
+                self.i += 1;
+                return Some(Node::Empty);
+            }
+            if self.peek() == Some(b':') {
+                self.i += 1;
+            }
+        }
+        let alternatives = self.alternatives();
+        self.i += 1;
+        self.verbose = verbose;
+        let group = Node::Group {
+            alternatives,
+            atomic,
+        };
+        Some(if look {
+            Node::Look(Box::new(group))
+        } else {
+            group
+        })
+    }
+}
+
+/// Returns `true` for the `?1`, `?+1` or `?-2` of a group call.
+fn is_group_call(rest: &[u8]) -> bool {
+    let Some(rest) = rest.strip_prefix(b"?") else {
+        return false;
+    };
+    let rest = rest
+        .strip_prefix(b"+")
+        .or(rest.strip_prefix(b"-"))
+        .unwrap_or(rest);
+    rest.first().is_some_and(u8::is_ascii_digit)
+}
+
+/// The character of a simple escape such as `\n` or `\.`.
+fn escaped_char(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        'f' => '\x0c',
+        'v' => '\x0b',
+        'a' => '\x07',
+        'e' => '\x1b',
+        '0' => '\0',
+        c => c,
+    }
+}
+
+#[derive(Default)]
+struct Linter {
+    warnings: Vec<LintWarning>,
+}
+
+impl Linter {
+    fn warn(&mut self, kind: LintKind, offset: usize) {
+        self.warnings.push(LintWarning { kind, offset });
+    }
+
+    fn sequence(&mut self, seq: &[Node], atomic: bool) {
+        for (k, node) in seq.iter().enumerate() {
+            if let Some((offset, chars)) = node.unbounded() {
+                for next in &seq[k + 1..] {
+                    if next
+                        .unbounded()
+                        .is_some_and(|(_, next)| next.intersects(chars))
+                    {
+                        self.warn(LintKind::AdjacentQuantifiers, offset);
+                        break;
+                    }
+                    if !next.nullable() {
+                        break;
+                    }
+                }
+            }
+            self.node(node, atomic);
+        }
+    }
+
+    fn node(&mut self, node: &Node, atomic: bool) {
+        match node {
+            Node::Look(node) => self.node(node, atomic),
+            Node::Group {
+                alternatives,
+                atomic: group_atomic,
+            } => {
+                for alternative in alternatives {
+                    self.sequence(alternative, atomic || *group_atomic);
+                }
+            }
+            Node::Repeat {
+                node: body,
+                max,
+                possessive,
+                offset,
+                ..
+            } => {
+                let atomic = atomic || *possessive;
+                if max.is_none_or(|max| max > 1) && !atomic {
+                    if let Node::Group { alternatives, .. } = &**body {
+                        let starts = alternatives
+                            .iter()
+                            .map(|seq| first(seq))
+                            .collect::<Vec<_>>();
+                        let overlap = starts.iter().enumerate().any(|(k, start)| {
+                            starts[k + 1..].iter().any(|other| other.intersects(*start))
+                        });
+                        if overlap {
+                            self.warn(LintKind::OverlappingAlternation, *offset);
+                        }
+                    }
+                    // The body can be followed by its own start in the next iteration.
+                    if ambiguous(body, body.first(), max.is_none()) {
+                        self.warn(LintKind::NestedQuantifier, *offset);
+                    }
+                }
+                self.node(body, atomic);
+            }
+            Node::Char(_) | Node::Empty | Node::Unknown => {}
+        }
+    }
+}
+
+/// Returns `true` if `node` contains a variable repetition, unbounded unless `unbounded` is set,
+/// that can also match a character in `follow`, which can come after `node`.
+fn ambiguous(node: &Node, follow: CharSet, unbounded: bool) -> bool {
+    match node {
+        Node::Repeat {
+            node,
+            min,
+            max,
+            possessive: false,
+            ..
+        } => {
+            let variable = Some(*min) != *max && max.is_none_or(|max| max > 1);
+            let repeats =
+                (variable && (unbounded || max.is_none())) && node.chars().intersects(follow);
+            let follow = if max.is_none_or(|max| max > 1) {
+                follow.union(node.first())
+            } else {
+                follow
+            };
+            repeats || ambiguous(node, follow, unbounded)
+        }
+        Node::Group {
+            alternatives,
+            atomic: false,
+        } => alternatives.iter().any(|seq| {
+            (0..seq.len()).any(|k| {
+                let rest = &seq[k + 1..];
+                let mut after = first(rest);
+                if nullable(rest) {
+                    after = after.union(follow);
+                }
+                ambiguous(&seq[k], after, unbounded)
+            })
+        }),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint() {
+        let kinds = |pattern| {
+            lint(pattern)
+                .into_iter()
+                .map(|warning| warning.kind)
+                .collect::<Vec<_>>()
+        };
+        for safe in [
+            r"^[\w.+-]+@\w+\.\w{2,}$",
+            r"(?:\d+,)*\d+",
+            r#"(?:"[^"]*"|\w+,)*"#,
+            r"(?:a|b)*c",
+            r"(a+)++ (?>(a+)+) \d+x\d+",
+            r"(?x) \d+ \# \d+ # \d+\d+",
+        ] {
+            assert_eq!(kinds(safe), [], "{safe}");
+        }
+        assert_eq!(kinds(r"^(a+)+$"), [LintKind::NestedQuantifier]);
+        assert_eq!(kinds(r"(\w+\s?)*"), [LintKind::NestedQuantifier]);
+        assert_eq!(kinds(r"(?:.*,)*x"), [LintKind::NestedQuantifier]);
+        assert_eq!(kinds(r"(?:\w|\d)+"), [LintKind::OverlappingAlternation]);
+        assert_eq!(kinds(r"\d+\.?\d*"), [LintKind::AdjacentQuantifiers]);
+        assert_eq!(lint(r"x(?:[a-z]|é)+(ab*b*)")[0].offset, 16);
+    }
+}
@@ -0,0 +1,28 @@
+/// Returns a `&'static PyRegex` that is compiled on first use and reused afterwards.
+///
+/// Panics with the pattern and the Python error if the pattern does not compile, so it is meant
+/// for patterns written in the source code rather than user input.
+///
+/// ```
+/// use py_regex::{Flags, lazy_pyregex};
+///
+/// fn is_id(text: &str) -> bool {
+///     lazy_pyregex!(r"^\d+$").is_match(text).unwrap()
+/// }
+///
+/// assert!(is_id("42"));
+/// assert!(lazy_pyregex!(r"^hello$", Flags::IGNORECASE).is_match("HELLO").unwrap());
+/// ```
+#[macro_export]
+macro_rules! lazy_pyregex {
+    ($pattern:expr $(,)?) => {
+        $crate::lazy_pyregex!($pattern, $crate::Flags::NONE)
+    };
+    ($pattern:expr, $flags:expr $(,)?) => {{
+        static RE: ::std::sync::OnceLock<$crate::PyRegex> = ::std::sync::OnceLock::new();
+        RE.get_or_init(|| {
+            $crate::PyRegex::with_flags($pattern, $flags)
+                .unwrap_or_else(|err| panic!("invalid pattern {:?}: {}", $pattern, err))
+        })
+    }};
+}
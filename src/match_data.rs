@@ -0,0 +1,144 @@
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::ops::{Index, Range};
+
+/// An owned copy of a match: the text and code point span of every group, plus the group names.
+/// Unlike `PyRegexMatch`, reading it never calls into Python.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchData {
+    groups: Vec<Option<(String, Range<usize>)>>,
+    names: HashMap<String, usize>,
+}
+
+impl MatchData {
+    /// Creates match data from the text and span of each group (starting with group 0) and the
+    /// group numbers of the named groups.
+    pub fn new(groups: Vec<Option<(String, Range<usize>)>>, names: HashMap<String, usize>) -> Self {
+        MatchData { groups, names }
+    }
+
+    /// Returns the text of the whole match.
+    pub fn as_str(&self) -> &str {
+        self.get(0).unwrap_or_default()
+    }
+
+    /// Returns the text of the group, or `None` if it did not participate or does not exist.
+    pub fn get(&self, group: usize) -> Option<&str> {
+        self.groups
+            .get(group)?
+            .as_ref()
+            .map(|(text, _)| text.as_str())
+    }
+
+    /// Returns the text of the named group, or `None` if it did not participate or does not exist.
+    pub fn name(&self, name: &str) -> Option<&str> {
+        self.get(*self.names.get(name)?)
+    }
+
+    /// Returns the code point span of the group, or `None` if it did not participate.
+    pub fn span(&self, group: usize) -> Option<Range<usize>> {
+        self.groups
+            .get(group)?
+            .as_ref()
+            .map(|(_, span)| span.clone())
+    }
+
+    /// Returns an iterator over the texts of groups 1 to N, with `None` for groups that did not
+    /// participate.
+    pub fn iter_groups(&self) -> impl ExactSizeIterator<Item = Option<&str>> {
+        self.groups
+            .iter()
+            .skip(1)
+            .map(|group| group.as_ref().map(|(text, _)| text.as_str()))
+    }
+
+    /// Returns the number of groups, including group 0.
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Returns `true` if there are no groups, which never happens for a match.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Returns the group numbers of the named groups.
+    pub fn names(&self) -> &HashMap<String, usize> {
+        &self.names
+    }
+}
+
+#[cfg(feature = "json")]
+impl MatchData {
+    /// Serializes the match as JSON, in the format described on the `Serialize` implementation.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("match data always serializes")
+    }
+}
+
+/// Serializes as `{ "match": ..., "span": [start, end], "groups": [...], "named": {...} }`, where
+/// `groups` holds groups 1 and up and groups that did not participate are `null`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MatchData {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let span = self.span(0).map(|span| [span.start, span.end]);
+        let groups = (1..self.len())
+            .map(|group| self.get(group))
+            .collect::<Vec<_>>();
+        let named = self
+            .names
+            .keys()
+            .map(|name| (name.as_str(), self.name(name)))
+            .collect::<BTreeMap<_, _>>();
+        let mut state = serializer.serialize_struct("MatchData", 4)?;
+        state.serialize_field("match", self.as_str())?;
+        state.serialize_field("span", &span)?;
+        state.serialize_field("groups", &groups)?;
+        state.serialize_field("named", &named)?;
+        state.end()
+    }
+}
+
+impl Index<usize> for MatchData {
+    type Output = str;
+
+    /// Returns the text of the group; panics if it did not participate.
+    fn index(&self, group: usize) -> &str {
+        self.get(group)
+            .unwrap_or_else(|| panic!("no group at index '{group}'"))
+    }
+}
+
+impl Index<&str> for MatchData {
+    type Output = str;
+
+    /// Returns the text of the named group; panics if it did not participate.
+    fn index(&self, name: &str) -> &str {
+        self.name(name)
+            .unwrap_or_else(|| panic!("no group named '{name}'"))
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let data = MatchData::new(
+            vec![
+                Some(("k=v".to_string(), 2..5)),
+                Some(("k".to_string(), 2..3)),
+                None,
+            ],
+            HashMap::from([("key".to_string(), 1)]),
+        );
+        assert_eq!(
+            data.to_json(),
+            r#"{"match":"k=v","span":[2,5],"groups":["k",null],"named":{"key":"k"}}"#
+        );
+    }
+}
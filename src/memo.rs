@@ -0,0 +1,190 @@
+use crate::haystack::PyText;
+use crate::python::PyRegex;
+use pyo3::PyResult;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A least-recently-used map from texts to results, with an optional time to live.
+struct Lru<V> {
+    entries: HashMap<String, (V, Instant, u64)>,
+    /// Keys by last use, oldest first.
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl<V: Clone> Lru<V> {
+    fn new() -> Self {
+        Lru {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    fn get(&mut self, text: &str, ttl: Option<Duration>) -> Option<V> {
+        let (value, inserted, last_used) = self.entries.get_mut(text)?;
+        if ttl.is_some_and(|ttl| inserted.elapsed() >= ttl) {
+            let last_used = *last_used;
+            self.order.remove(&last_used);
+            self.entries.remove(text);
+            return None;
+        }
+        self.tick += 1;
+        let key = self
+            .order
+            .remove(last_used)
+            .expect("entries and order agree");
+        *last_used = self.tick;
+        self.order.insert(self.tick, key);
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, text: &str, value: V, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        if let Some((_, _, last_used)) = self.entries.remove(text) {
+            self.order.remove(&last_used);
+        }
+        while self.entries.len() >= capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        self.tick += 1;
+        self.entries
+            .insert(text.to_string(), (value, Instant::now(), self.tick));
+        self.order.insert(self.tick, text.to_string());
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// A pattern that remembers the results of `is_match` and `find_all` for recently seen texts,
+/// for workloads that check the same strings over and over.
+///
+/// Each method keeps up to `capacity` texts and evicts the least recently used one beyond that.
+/// Errors are not remembered. The lock is not held while Python runs. Texts are remembered by
+/// their contents, so they are Rust strings: `&str`, `&String`, `&Arc<str>` and the like.
+///
+/// ```
+/// use py_regex::{Memoized, PyRegex};
+/// use std::time::Duration;
+///
+/// let re = Memoized::new(PyRegex::new(r"^[a-z]+$")?, 10_000).with_ttl(Duration::from_secs(60));
+/// assert!(re.is_match("abc")?);
+/// assert!(re.is_match("abc")?); // no Python call
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+pub struct Memoized {
+    re: PyRegex,
+    capacity: usize,
+    ttl: Option<Duration>,
+    is_match: Mutex<Lru<bool>>,
+    find_all: Mutex<Lru<Vec<String>>>,
+}
+
+impl Memoized {
+    /// Wraps the pattern with caches of `capacity` texts per method.
+    pub fn new(re: PyRegex, capacity: usize) -> Self {
+        Memoized {
+            re,
+            capacity,
+            ttl: None,
+            is_match: Mutex::new(Lru::new()),
+            find_all: Mutex::new(Lru::new()),
+        }
+    }
+
+    /// Forgets results once they are older than `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Returns the wrapped pattern.
+    pub fn regex(&self) -> &PyRegex {
+        &self.re
+    }
+
+    /// Same as `PyRegex::is_match`, remembered per text.
+    pub fn is_match<T: PyText + AsRef<str> + ?Sized>(&self, text: &T) -> PyResult<bool> {
+        self.memoize(&self.is_match, text.as_ref(), |re| re.is_match(text))
+    }
+
+    /// Same as `PyRegex::find_all`, remembered per text.
+    pub fn find_all<T: PyText + AsRef<str> + ?Sized>(&self, text: &T) -> PyResult<Vec<String>> {
+        self.memoize(&self.find_all, text.as_ref(), |re| re.find_all(text))
+    }
+
+    /// Forgets all remembered results.
+    pub fn clear(&self) {
+        self.is_match.lock().unwrap().clear();
+        self.find_all.lock().unwrap().clear();
+    }
+
+    fn memoize<V: Clone>(
+        &self,
+        cache: &Mutex<Lru<V>>,
+        text: &str,
+        compute: impl FnOnce(&PyRegex) -> PyResult<V>,
+    ) -> PyResult<V> {
+        if let Some(value) = cache.lock().unwrap().get(text, self.ttl) {
+            return Ok(value);
+        }
+        let value = compute(&self.re)?;
+        cache
+            .lock()
+            .unwrap()
+            .insert(text, value.clone(), self.capacity);
+        Ok(value)
+    }
+}
+
+impl fmt::Debug for Memoized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Memoized")
+            .field("re", &self.re)
+            .field("capacity", &self.capacity)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru() {
+        let mut lru = Lru::new();
+        lru.insert("a", 1, 2);
+        lru.insert("b", 2, 2);
+        assert_eq!(lru.get("a", None), Some(1));
+        lru.insert("c", 3, 2);
+        assert_eq!(lru.get("b", None), None);
+        assert_eq!(lru.get("a", None), Some(1));
+        assert_eq!(lru.get("c", Some(Duration::ZERO)), None);
+        assert_eq!(lru.entries.len(), lru.order.len());
+    }
+
+    #[test]
+    fn test_memoized() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = Memoized::new(PyRegex::new(r"\d+")?, 8);
+        assert_eq!(re.find_all("a1 b22")?, ["1", "22"]);
+        assert_eq!(re.find_all("a1 b22")?, ["1", "22"]);
+        assert!(!re.is_match("abc")?);
+        re.clear();
+        assert!(re.is_match("7")?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! Per-pattern operation counters, enabled by the `metrics` feature.
+//!
+//! Every `PyRegex` handle counts its matching operations, the matches they found, the timeouts
+//! and errors they ran into and the time they took. Clones of a handle, including the handles
+//! returned by `PyRegex::cached`, share the counters. `PyRegex::stats` reads them for one handle
+//! and `snapshot` for every live handle, e.g. to find ReDoS-prone rules:
+//!
+//! ```
+//! let mut slowest = py_regex::metrics::snapshot();
+//! slowest.sort_by_key(|entry| std::cmp::Reverse(entry.stats.total_time));
+//! for entry in slowest.iter().take(5) {
+//!     println!("{:?}: {:?}", entry.pattern, entry.stats);
+//! }
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// Counters of a pattern handle at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Matching operations performed (`search_match`, `is_match`, `find_iter`, `find_all`,
+    /// `replace`, `split`).
+    pub operations: u64,
+    /// Matches found by `search_match`, `is_match` and `find_iter`.
+    pub matches: u64,
+    /// Operations aborted by the timeout set with `PyRegex::with_timeout`.
+    pub timeouts: u64,
+    /// Operations that failed for another reason.
+    pub errors: u64,
+    /// Time spent in the operations, including waiting for the GIL.
+    pub total_time: Duration,
+}
+
+/// The counters of a live pattern handle, as returned by `snapshot`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternStats {
+    /// The source pattern of the handle.
+    pub pattern: String,
+    /// Its counters.
+    pub stats: Stats,
+}
+
+/// Counters shared by the clones of a pattern handle.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    operations: AtomicU64,
+    matches: AtomicU64,
+    timeouts: AtomicU64,
+    errors: AtomicU64,
+    nanos: AtomicU64,
+}
+
+/// Counters of every handle created so far, pruned of dropped handles on `snapshot`.
+static REGISTRY: Mutex<Vec<(String, Weak<Counters>)>> = Mutex::new(Vec::new());
+
+impl Counters {
+    /// Creates the counters of a new handle and adds them to the global registry.
+    pub(crate) fn register(pattern: String) -> Arc<Counters> {
+        let counters = Arc::new(Counters::default());
+        REGISTRY
+            .lock()
+            .unwrap()
+            .push((pattern, Arc::downgrade(&counters)));
+        counters
+    }
+
+    /// Records an operation that took `elapsed`.
+    pub(crate) fn record(&self, elapsed: Duration, matches: usize, outcome: Outcome) {
+        self.operations.fetch_add(1, Ordering::Relaxed);
+        self.matches.fetch_add(matches as u64, Ordering::Relaxed);
+        self.nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+        match outcome {
+            Outcome::Ok => {}
+            Outcome::Timeout => {
+                self.timeouts.fetch_add(1, Ordering::Relaxed);
+            }
+            Outcome::Error => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Reads the counters.
+    pub(crate) fn stats(&self) -> Stats {
+        Stats {
+            operations: self.operations.load(Ordering::Relaxed),
+            matches: self.matches.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            total_time: Duration::from_nanos(self.nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// How an operation ended.
+pub(crate) enum Outcome {
+    Ok,
+    Timeout,
+    Error,
+}
+
+/// Returns the counters of every live pattern handle.
+pub fn snapshot() -> Vec<PatternStats> {
+    let mut registry = REGISTRY.lock().unwrap();
+    registry.retain(|(_, counters)| counters.strong_count() > 0);
+    registry
+        .iter()
+        .filter_map(|(pattern, counters)| {
+            Some(PatternStats {
+                pattern: pattern.clone(),
+                stats: counters.upgrade()?.stats(),
+            })
+        })
+        .collect()
+}
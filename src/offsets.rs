@@ -0,0 +1,68 @@
+/// Converts a position counted in code points, as reported by Python, into a UTF-8 byte offset
+/// into `text`, so it can be used to slice the haystack.
+/// Returns `None` if the position lies beyond the end of `text`.
+pub fn char_to_byte_offset(text: &str, char_index: usize) -> Option<usize> {
+    text.char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(text.len()))
+        .nth(char_index)
+}
+
+/// Converts a position counted in code points into a byte offset into a byte string that was
+/// searched as a `BStr` haystack, where each byte of an invalid UTF-8 sequence counts as one code
+/// point, as with Python's `surrogateescape`.
+/// Returns `None` if the position lies beyond the end of `text`.
+#[cfg(feature = "bstr")]
+pub fn bstr_char_to_byte_offset(text: &bstr::BStr, char_index: usize) -> Option<usize> {
+    use bstr::ByteSlice;
+
+    text.char_indices()
+        .flat_map(|(start, end, c)| {
+            let invalid =
+                c == char::REPLACEMENT_CHARACTER && text[start..end] != *"\u{fffd}".as_bytes();
+            let step = if invalid { 1 } else { end - start };
+            (start..end).step_by(step)
+        })
+        .chain(std::iter::once(text.len()))
+        .nth(char_index)
+}
+
+/// Converts code point positions within one haystack into byte offsets, in constant time after
+/// a single pass over the text (or none at all for ASCII text).
+#[cfg(any(feature = "python", feature = "fallback-fancy-regex"))]
+pub(crate) struct ByteOffsets {
+    offsets: Option<Vec<usize>>,
+}
+
+#[cfg(any(feature = "python", feature = "fallback-fancy-regex"))]
+impl ByteOffsets {
+    pub(crate) fn new(text: &str) -> Self {
+        ByteOffsets {
+            offsets: (!text.is_ascii()).then(|| {
+                text.char_indices()
+                    .map(|(offset, _)| offset)
+                    .chain(std::iter::once(text.len()))
+                    .collect()
+            }),
+        }
+    }
+
+    /// Returns the byte offset of the given code point position.
+    #[cfg(feature = "python")]
+    pub(crate) fn get(&self, char_index: usize) -> usize {
+        match &self.offsets {
+            Some(offsets) => offsets[char_index],
+            None => char_index,
+        }
+    }
+
+    /// Returns the code point position of the given byte offset, which must lie on a character
+    /// boundary, in logarithmic time.
+    #[cfg(feature = "fallback-fancy-regex")]
+    pub(crate) fn char_index(&self, byte: usize) -> usize {
+        match &self.offsets {
+            Some(offsets) => offsets.partition_point(|&offset| offset < byte),
+            None => byte,
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Vetted patterns for common formats, compiled on first use through the pattern cache.
+//!
+//! The patterns search for occurrences inside larger texts. To check that a whole value has the
+//! format, use the constants with `Validator::field`, which anchors them.
+//!
+//! ```
+//! use py_regex::patterns;
+//!
+//! let ips = patterns::ipv4()?.find_all("from 10.0.0.1 to 10.0.0.256")?;
+//! assert_eq!(ips, ["10.0.0.1"]);
+//! # Ok::<(), pyo3::PyErr>(())
+//! ```
+
+use crate::python::PyRegex;
+use pyo3::PyResult;
+use std::sync::Arc;
+
+/// An email address: a dot-atom local part and a domain of at least two labels. Quoted local
+/// parts and IP literals are not supported.
+pub const EMAIL: &str = concat!(
+    r"(?<![\w.!#$%&'*+/=?^`{|}~-])",
+    r"[\w!#$%&'*+/=?^`{|}~-]+(?:\.[\w!#$%&'*+/=?^`{|}~-]+)*",
+    r"@(?:[^\W_](?:[\w-]{0,61}[^\W_])?\.)+[^\W\d_]{2,63}\b",
+);
+
+/// An `http` or `https` URL with a host name, IPv4 or bracketed IPv6 host, an optional port and
+/// an optional path, query and fragment. Trailing punctuation such as a sentence's final period
+/// or a closing parenthesis is not included.
+pub const URL: &str = concat!(
+    r"\bhttps?://",
+    r"(?:[^\W_](?:[\w-]{0,61}[^\W_])?(?:\.[^\W_](?:[\w-]{0,61}[^\W_])?)*|\[[0-9A-Fa-f:.]+\])",
+    r"(?::\d{1,5})?",
+    r"(?:[/?#](?:[^\s<>]*[^\s<>.,;:!?'()\[\]{}])?)?",
+);
+
+/// A dotted-decimal IPv4 address, without leading zeros.
+pub const IPV4: &str = concat!(
+    r"(?<![\w.])",
+    r"(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)",
+    r"(?!\.?\w)",
+);
+
+/// An IPv6 address in full or `::`-compressed form. Forms with an embedded IPv4 address and
+/// zone indexes are not supported.
+pub const IPV6: &str = concat!(
+    r"(?<![\w:.])(?:",
+    r"(?:[0-9A-Fa-f]{1,4}:){7}[0-9A-Fa-f]{1,4}",
+    r"|(?:[0-9A-Fa-f]{1,4}:){1,7}:",
+    r"|(?:[0-9A-Fa-f]{1,4}:){1,6}:[0-9A-Fa-f]{1,4}",
+    r"|(?:[0-9A-Fa-f]{1,4}:){1,5}(?::[0-9A-Fa-f]{1,4}){1,2}",
+    r"|(?:[0-9A-Fa-f]{1,4}:){1,4}(?::[0-9A-Fa-f]{1,4}){1,3}",
+    r"|(?:[0-9A-Fa-f]{1,4}:){1,3}(?::[0-9A-Fa-f]{1,4}){1,4}",
+    r"|(?:[0-9A-Fa-f]{1,4}:){1,2}(?::[0-9A-Fa-f]{1,4}){1,5}",
+    r"|[0-9A-Fa-f]{1,4}:(?::[0-9A-Fa-f]{1,4}){1,6}",
+    r"|:(?:(?::[0-9A-Fa-f]{1,4}){1,7}|:)",
+    r")(?![\w:]|\.\w)",
+);
+
+/// A UUID in the canonical hyphenated form, in either case.
+pub const UUID: &str =
+    r"\b[0-9A-Fa-f]{8}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{12}\b";
+
+/// An ISO 8601 calendar date (`YYYY-MM-DD`), with the month and day ranges checked but not the
+/// number of days in the month.
+pub const ISO_DATE: &str = r"(?<![\w-])\d{4}-(?:0[1-9]|1[0-2])-(?:0[1-9]|[12]\d|3[01])(?![\w-])";
+
+/// A semantic version as specified by semver.org, with the named groups `major`, `minor`,
+/// `patch`, `prerelease` and `buildmetadata`. A leading `v` is allowed but not part of the match.
+pub const SEMVER: &str = concat!(
+    r"(?:(?<=\bv)|(?<![\w.+-]))",
+    r"(?P<major>0|[1-9]\d*)\.(?P<minor>0|[1-9]\d*)\.(?P<patch>0|[1-9]\d*)",
+    r"(?:-(?P<prerelease>(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)",
+    r"(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?",
+    r"(?:\+(?P<buildmetadata>[0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?",
+    r"(?![\w+-]|\.\w)",
+);
+
+/// Returns the compiled `EMAIL` pattern.
+pub fn email() -> PyResult<Arc<PyRegex>> {
+    PyRegex::cached(EMAIL)
+}
+
+/// Returns the compiled `URL` pattern.
+pub fn url() -> PyResult<Arc<PyRegex>> {
+    PyRegex::cached(URL)
+}
+
+/// Returns the compiled `IPV4` pattern.
+pub fn ipv4() -> PyResult<Arc<PyRegex>> {
+    PyRegex::cached(IPV4)
+}
+
+/// Returns the compiled `IPV6` pattern.
+pub fn ipv6() -> PyResult<Arc<PyRegex>> {
+    PyRegex::cached(IPV6)
+}
+
+/// Returns the compiled `UUID` pattern.
+pub fn uuid() -> PyResult<Arc<PyRegex>> {
+    PyRegex::cached(UUID)
+}
+
+/// Returns the compiled `ISO_DATE` pattern.
+pub fn iso_date() -> PyResult<Arc<PyRegex>> {
+    PyRegex::cached(ISO_DATE)
+}
+
+/// Returns the compiled `SEMVER` pattern.
+pub fn semver() -> PyResult<Arc<PyRegex>> {
+    PyRegex::cached(SEMVER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        assert_eq!(
+            email()?.find_all("mail a.b+c@mail.example.org, not a@b or .x@y.com.")?,
+            ["a.b+c@mail.example.org"]
+        );
+        assert_eq!(
+            url()?.find_all("see (https://example.com/a?b=1). or http://[::1]:8080/")?,
+            ["https://example.com/a?b=1", "http://[::1]:8080/"]
+        );
+        assert_eq!(
+            ipv4()?.find_all("10.0.0.1, 1.2.3.4.5, 256.1.1.1, 01.2.3.4 and 192.168.0.255.")?,
+            ["10.0.0.1", "192.168.0.255"]
+        );
+        assert_eq!(
+            ipv6()?.find_all("::1 fe80::1:2 2001:db8::8a2e:370:7334 1:2:3:4:5:6:7:8 1:::2")?,
+            [
+                "::1",
+                "fe80::1:2",
+                "2001:db8::8a2e:370:7334",
+                "1:2:3:4:5:6:7:8"
+            ]
+        );
+        assert!(uuid()?.is_match("id 123E4567-e89b-12d3-a456-426614174000")?);
+        assert_eq!(
+            iso_date()?.find_all("2024-02-29 2024-13-01 2024-1-01")?,
+            ["2024-02-29"]
+        );
+        let m = semver()?
+            .search_match("v1.0.0-rc.1+build.5 and 1.2")?
+            .unwrap()
+            .to_data()?;
+        assert_eq!(m.as_str(), "1.0.0-rc.1+build.5");
+        assert_eq!(&m["prerelease"], "rc.1");
+        assert_eq!(&m["buildmetadata"], "build.5");
+        assert!(!semver()?.is_match("1.2.03")?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use crate::cancel::CancellationToken;
+use crate::group::Group;
+use crate::python::PyRegex;
+use pyo3::exceptions::PyIndexError;
+use pyo3::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+
+type Transform = Arc<dyn Fn(String) -> String + Send + Sync>;
+
+#[derive(Clone)]
+struct Stage {
+    re: PyRegex,
+    /// The number of the group passed on, resolved from its name when the stage is added.
+    group: usize,
+    transform: Option<Transform>,
+}
+
+/// A chain of extraction stages. Each stage finds all matches of its pattern in every item
+/// produced by the previous stage (the input text for the first one), selects a group from each
+/// match and optionally transforms it. Groups that did not participate are skipped.
+///
+/// The whole chain runs in a single GIL session.
+///
+/// ```
+/// use py_regex::Pipeline;
+///
+/// let hosts = Pipeline::new()
+///     .stage(r"https?://\S+", 0)?
+///     .stage_map(r"^\w+://(?P<host>[^/:]+)", "host", |host| host.to_lowercase())?
+///     .run("see https://Example.com/a and http://docs.rs:80")?;
+/// assert_eq!(hosts, ["example.com", "docs.rs"]);
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl Pipeline {
+    /// Creates a pipeline without stages, which returns its input as the only item.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a stage passing on the selected group of each match. Returns an `IndexError` if the
+    /// pattern has no such group.
+    pub fn stage<'a>(self, pattern: &str, group: impl Into<Group<'a>>) -> PyResult<Self> {
+        self.push(pattern, group.into(), None)
+    }
+
+    /// Adds a stage passing on the selected group of each match, transformed by `transform`.
+    pub fn stage_map<'a, F>(
+        self,
+        pattern: &str,
+        group: impl Into<Group<'a>>,
+        transform: F,
+    ) -> PyResult<Self>
+    where
+        F: Fn(String) -> String + Send + Sync + 'static,
+    {
+        self.push(pattern, group.into(), Some(Arc::new(transform)))
+    }
+
+    /// Makes `run` check `token` before each item of each stage and fail with a `Cancelled` error
+    /// once it is cancelled. See `CancellationToken`.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    fn push(mut self, pattern: &str, group: Group, transform: Option<Transform>) -> PyResult<Self> {
+        let re = PyRegex::new(pattern)?;
+        let number = match group {
+            Group::Index(index) => (index <= re.groups_len()?).then_some(index),
+            Group::Name(name) => re.group_index()?.get(name).copied(),
+        };
+        let Some(group) = number else {
+            return Err(PyIndexError::new_err(format!("no such group: {group}")));
+        };
+        self.stages.push(Stage {
+            re,
+            group,
+            transform,
+        });
+        Ok(self)
+    }
+
+    /// Runs the stages over the text and returns the items produced by the last one.
+    pub fn run(&self, text: &str) -> PyResult<Vec<String>> {
+        Python::with_gil(|py| {
+            let mut items = vec![text.to_string()];
+            for stage in &self.stages {
+                let finditer = stage.re.as_py().bind(py).getattr("finditer")?;
+                let kwargs = stage.re.kwargs(py);
+                let mut next = Vec::new();
+                for item in &items {
+                    if let Some(token) = &self.cancellation {
+                        token.check()?;
+                    }
+                    for m in finditer.call((item,), kwargs.as_ref())?.try_iter()? {
+                        let m = m?;
+                        let selected = m.call_method1("group", (stage.group,))?;
+                        let Some(selected) = selected.extract::<Option<String>>()? else {
+                            continue;
+                        };
+                        next.push(match &stage.transform {
+                            Some(transform) => transform(selected),
+                            None => selected,
+                        });
+                    }
+                }
+                items = next;
+            }
+            Ok(items)
+        })
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.stages.iter().map(|stage| (&stage.re, &stage.group)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let hosts = Pipeline::new()
+            .stage(r"https?://\S+", 0)?
+            .stage_map(r"^\w+://(?P<host>[^/:]+)", "host", |host| {
+                host.to_lowercase()
+            })?
+            .run("see https://Example.com/a, http://docs.rs:80 and ftp://x")?;
+        assert_eq!(hosts, ["example.com", "docs.rs"]);
+        assert_eq!(Pipeline::new().run("x")?, ["x"]);
+        assert!(Pipeline::new().stage("(", 0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_groups() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let pipeline = Pipeline::new().stage(r"(?P<key>\w+)=(\w+)", 2u8)?;
+        assert_eq!(pipeline.run("a=1 b=2")?, ["1", "2"]);
+        let pipeline = Pipeline::new().stage(r"(?P<key>\w+)=(\w+)?", "key")?;
+        assert_eq!(pipeline.run("a=1 b=")?, ["a", "b"]);
+        assert!(Pipeline::new().stage("(a)", 2).is_err());
+        assert!(Pipeline::new().stage("(?P<a>a)", "b").is_err());
+
+        Ok(())
+    }
+}
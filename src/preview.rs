@@ -0,0 +1,89 @@
+use crate::haystack::PyText;
+use crate::helpers::helpers;
+use crate::python::PyRegex;
+use pyo3::prelude::*;
+use std::ops::Range;
+
+/// One change that `PyRegex::replace` would make, as returned by `PyRegex::replace_preview`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+    /// The code point span of the replaced text.
+    pub span: Range<usize>,
+    /// The text that would be replaced.
+    pub old: String,
+    /// The expanded replacement.
+    pub new: String,
+}
+
+impl PyRegex {
+    /// Returns the changes that `replace` would make, in order, without building the new text,
+    /// e.g. to show users what a substitution does before applying it.
+    ///
+    /// ```
+    /// let re = py_regex::PyRegex::new(r"(\w+)@example\.org")?;
+    /// let changes = re.replace_preview("mail bob@example.org", r"\1@example.com")?;
+    /// assert_eq!((changes[0].span.clone(), changes[0].new.as_str()), (5..20, "bob@example.com"));
+    /// # Ok::<(), pyo3::PyErr>(())
+    /// ```
+    pub fn replace_preview<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+        replacement: impl AsRef<str>,
+    ) -> PyResult<Vec<Substitution>> {
+        Python::with_gil(|py| {
+            let changes = helpers(py)?.call_method1(
+                "preview_sub",
+                (
+                    self.as_py(),
+                    replacement.as_ref(),
+                    text.to_py_str(py),
+                    self.kwargs(py),
+                ),
+            )?;
+            Ok(changes
+                .extract::<Vec<((usize, usize), String, String)>>()?
+                .into_iter()
+                .map(|((start, end), old, new)| Substitution {
+                    span: start..end,
+                    old,
+                    new,
+                })
+                .collect())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_preview() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(?P<n>\d+)|x*")?;
+        let text = "é1 22";
+        let changes = re.replace_preview(text, r"<\g<n>>")?;
+        let spans = changes.iter().map(|c| c.span.clone()).collect::<Vec<_>>();
+        assert_eq!(spans, [0..0, 1..2, 2..2, 3..5, 5..5]);
+        assert_eq!(
+            (changes[3].old.as_str(), changes[3].new.as_str()),
+            ("22", "<22>")
+        );
+        assert_eq!(changes[0].new, "<>");
+
+        // Applying the changes gives the same text as `replace`.
+        let mut applied = String::new();
+        let mut last = 0;
+        let chars = text.chars().collect::<Vec<_>>();
+        for change in &changes {
+            applied.extend(&chars[last..change.span.start]);
+            applied.push_str(&change.new);
+            last = change.span.end;
+        }
+        applied.extend(&chars[last..]);
+        assert_eq!(applied, re.replace(text, r"<\g<n>>")?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use crate::backend::{self, Feature};
+use pyo3::prelude::*;
+use std::ops::RangeInclusive;
+
+/// Returns `true` if the installed `regex` module knows the Unicode property, as written inside
+/// `\p{...}`: a name such as `Greek` or `Lu`, or a `Name=Value` pair such as `Script=Greek`.
+///
+/// Checking property names when rules are loaded gives a clear error up front instead of a
+/// compile error later. Returns `UnsupportedFeature` on the `re` fallback.
+///
+/// ```
+/// assert!(py_regex::supports_property("Script=Greek")?);
+/// assert!(!py_regex::supports_property("Script=Elvish")?);
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+pub fn supports_property(name: &str) -> PyResult<bool> {
+    Python::with_gil(|py| Ok(compile_property(py, name, "")?.is_some()))
+}
+
+/// Returns the characters with the Unicode property, as sorted, disjoint ranges, or a
+/// `ValueError` if the property is unknown.
+pub fn property_ranges(name: &str) -> PyResult<Vec<RangeInclusive<char>>> {
+    // Every scalar value once, in order, so that text positions map back to code points.
+    let all = (0..=char::MAX as u32)
+        .filter_map(char::from_u32)
+        .collect::<String>();
+    let to_char = |index: usize| {
+        let code = index as u32;
+        let code = if code < 0xd800 { code } else { code + 0x800 };
+        char::from_u32(code).expect("index of a scalar value")
+    };
+    Python::with_gil(|py| {
+        let Some(compiled) = compile_property(py, name, "+")? else {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown Unicode property `{name}`"
+            )));
+        };
+        let mut ranges = Vec::new();
+        for m in compiled.call_method1("finditer", (all,))?.try_iter()? {
+            let (start, end) = m?.call_method0("span")?.extract::<(usize, usize)>()?;
+            ranges.push(to_char(start)..=to_char(end - 1));
+        }
+        Ok(ranges)
+    })
+}
+
+/// Compiles `\p{name}` followed by `suffix`, or returns `None` if the name is not a property.
+fn compile_property<'py>(
+    py: Python<'py>,
+    name: &str,
+    suffix: &str,
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    backend::require(py, Feature::UnicodeProperties)?;
+    if name.is_empty() || name.contains(['{', '}', '\\']) {
+        return Ok(None);
+    }
+    let module = backend::module(py)?;
+    match module.call_method1("compile", (format!(r"\p{{{name}}}{suffix}"),)) {
+        Ok(compiled) => Ok(Some(compiled)),
+        Err(err) if err.is_instance(py, &module.getattr("error")?) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_properties() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        for name in ["Greek", "Script=Greek", "sc=Grek", "Lu", "Alphabetic"] {
+            assert!(supports_property(name)?, "{name}");
+        }
+        for name in ["", "Elvish", "Script=Elvish", "L}|x"] {
+            assert!(!supports_property(name)?, "{name}");
+        }
+
+        let greek = property_ranges("Script=Greek")?;
+        assert!(greek.iter().any(|range| range.contains(&'α')));
+        assert!(!greek.iter().any(|range| range.contains(&'a')));
+        let surrogates = property_ranges("Cs")?;
+        assert!(surrogates.is_empty());
+        assert_eq!(property_ranges("Nd")?[0], '0'..='9');
+        assert!(property_ranges("Elvish").is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1963 @@
+use crate::backend::{self, Backend, Feature};
+use crate::builder::PyRegexBuilder;
+use crate::cancel::CancellationToken;
+use crate::flags::{Flags, Version};
+use crate::from_captures::{FromCaptures, parse_capture};
+use crate::group::Group;
+use crate::haystack::PyText;
+use crate::helpers::helpers;
+use crate::match_data::MatchData;
+#[cfg(feature = "metrics")]
+use crate::metrics::{Counters, Stats};
+use crate::offsets::{ByteOffsets, char_to_byte_offset};
+use crate::trace;
+use pyo3::PyResult;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyAny, PyBytes, PyDict, PyIterator, PyModule};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Detects fuzzy constraints such as `{e<=2}` or `{i<=1,d<=1}`, but not `\p{...}` properties.
+const FUZZY_SYNTAX: &str = r"(?<!\\[pPN])\{[^{}]*[eids][^{}]*\}";
+
+/// Flags that Python adds to every compiled `str` pattern on its own (`UNICODE` and the default
+/// `VERSION0`), which are noise when displaying a pattern.
+const IMPLICIT_FLAGS: Flags = Flags::from_bits(0x20 | 0x2000);
+
+/// Flags that the `re` module understands as well.
+const RE_FLAGS: Flags = Flags::from_bits(
+    Flags::ASCII.bits()
+        | Flags::IGNORECASE.bits()
+        | Flags::LOCALE.bits()
+        | Flags::MULTILINE.bits()
+        | Flags::DOTALL.bits()
+        | Flags::UNICODE.bits()
+        | Flags::VERBOSE.bits(),
+);
+
+type PatternCache = Mutex<HashMap<(String, Flags), Arc<PyRegex>>>;
+
+/// Process-wide cache of compiled patterns used by `PyRegex::cached`.
+static CACHE: OnceLock<PatternCache> = OnceLock::new();
+
+/// Clears the `regex` module's internal pattern cache (`regex.purge()`) along with the cache
+/// behind `PyRegex::cached`. Handles that were already returned remain valid.
+pub fn purge() -> PyResult<()> {
+    if let Some(cache) = CACHE.get() {
+        cache.lock().unwrap().clear();
+    }
+    Python::with_gil(|py| {
+        backend::module(py)?.call_method0("purge")?;
+        Ok(())
+    })
+}
+
+/// Sets whether the `regex` module caches every compiled pattern (`regex.cache_all()`).
+/// Disabling it keeps the Python-side cache from growing with one-off patterns.
+/// Does nothing on the `re` backend, whose cache is always bounded.
+pub fn set_cache_all(value: bool) -> PyResult<()> {
+    Python::with_gil(|py| {
+        let module = backend::module(py)?;
+        if backend::active() == Backend::Regex {
+            module.call_method1("cache_all", (value,))?;
+        }
+        Ok(())
+    })
+}
+
+/// Sets the behaviour version used by patterns that select none with their flags
+/// (`regex.DEFAULT_VERSION`). Applies to patterns compiled afterwards, so the cache behind
+/// `PyRegex::cached` is cleared. `Version::V1` returns `UnsupportedFeature` on the `re` backend.
+pub fn set_default_version(version: Version) -> PyResult<()> {
+    Python::with_gil(|py| {
+        let module = backend::module(py)?;
+        if backend::active() == Backend::Re {
+            if version == Version::V1 {
+                backend::require(py, Feature::Version1)?;
+            }
+            return Ok(());
+        }
+        module.setattr("DEFAULT_VERSION", version.flags().bits())?;
+        if let Some(cache) = CACHE.get() {
+            cache.lock().unwrap().clear();
+        }
+        Ok(())
+    })
+}
+
+/// Searches `text` for the first match of `pattern`, compiling it through the global cache.
+pub fn search<T: PyText + ?Sized>(pattern: &str, text: &T) -> PyResult<Option<PyRegexMatch>> {
+    PyRegex::cached(pattern)?.search_match(text)
+}
+
+/// Returns all matches of `pattern` in `text` like Python's `regex.findall`,
+/// compiling it through the global cache.
+pub fn findall<T: PyText + ?Sized>(pattern: &str, text: &T) -> PyResult<Vec<String>> {
+    PyRegex::cached(pattern)?.find_all(text)
+}
+
+/// Replaces all matches of `pattern` in `text` like Python's `regex.sub`,
+/// compiling it through the global cache.
+pub fn sub<T: PyText + ?Sized>(
+    pattern: &str,
+    replacement: impl AsRef<str>,
+    text: &T,
+) -> PyResult<String> {
+    PyRegex::cached(pattern)?.replace(text, replacement)
+}
+
+/// Splits `text` by the matches of `pattern` like Python's `regex.split`,
+/// compiling it through the global cache.
+pub fn split<T: PyText + ?Sized>(pattern: &str, text: &T) -> PyResult<Vec<String>> {
+    PyRegex::cached(pattern)?.split(text)
+}
+
+/// Folds the case of `text` the way the `regex` module does for `FULLCASE` with `IGNORECASE`
+/// matching, e.g. to normalize keys so that deduplication agrees with case-insensitive matching.
+/// This differs from `str::to_lowercase` (`ß` folds to `ss`) and from Python's `str.casefold`
+/// (`İ` is kept). Returns `UnsupportedFeature` on the `re` backend.
+pub fn casefold(text: &str) -> PyResult<String> {
+    Python::with_gil(|py| {
+        backend::require(py, Feature::Flag(Flags::FULLCASE))?;
+        let flags = (Flags::FULLCASE | Flags::IGNORECASE).bits();
+        py.import("regex._regex")?
+            .call_method1("fold_case", (flags, text))?
+            .extract()
+    })
+}
+
+/// Converts the result of `match_data` in `helpers.py` into `MatchData`.
+pub(crate) fn match_data_from(parts: &Bound<PyAny>) -> PyResult<MatchData> {
+    let (texts, regs, names) = parts.extract::<(
+        Vec<Option<String>>,
+        Vec<(isize, isize)>,
+        HashMap<String, usize>,
+    )>()?;
+    let groups = texts
+        .into_iter()
+        .zip(regs)
+        .map(|(text, (start, end))| Some((text?, start as usize..end as usize)))
+        .collect();
+    Ok(MatchData::new(groups, names))
+}
+
+/// Appends the formatted Python traceback, which ends with the qualified exception type name, to
+/// the message of an error raised by Python code. The result has the same type and the original
+/// error as its cause. Errors without a traceback are returned unchanged.
+pub(crate) fn with_traceback(err: PyErr) -> PyErr {
+    Python::with_gil(|py| {
+        let Some(traceback) = err.traceback(py) else {
+            return err;
+        };
+        let detailed = || -> PyResult<PyErr> {
+            let traceback = PyModule::import(py, "traceback")?
+                .call_method1(
+                    "format_exception",
+                    (err.get_type(py), err.value(py), traceback),
+                )?
+                .extract::<Vec<String>>()?
+                .concat();
+            let message = format!("{}\n\n{}", err.value(py).str()?, traceback.trim_end());
+            let detailed = PyErr::from_value(err.get_type(py).call1((message,))?);
+            detailed.set_cause(py, Some(err.clone_ref(py)));
+            Ok(detailed)
+        };
+        detailed().unwrap_or(err)
+    })
+}
+
+/// Reads `Pattern.groupindex`, which is a mapping proxy rather than a dict on the `re` backend.
+fn groupindex(pattern: &Bound<PyAny>) -> PyResult<HashMap<String, usize>> {
+    PyDict::from_sequence(&pattern.getattr("groupindex")?.call_method0("items")?)?
+        .extract::<HashMap<String, usize>>()
+}
+
+/// A wrapper for a compiled regular expression from the Python `regex` library.
+pub struct PyRegex {
+    compiled: Py<PyAny>,
+    timeout: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+    #[cfg(feature = "metrics")]
+    counters: Arc<Counters>,
+}
+
+impl Clone for PyRegex {
+    /// Clones the handle; both refer to the same compiled Python pattern object.
+    fn clone(&self) -> Self {
+        Python::with_gil(|py| PyRegex {
+            compiled: self.compiled.clone_ref(py),
+            timeout: self.timeout,
+            cancellation: self.cancellation.clone(),
+            #[cfg(feature = "metrics")]
+            counters: Arc::clone(&self.counters),
+        })
+    }
+}
+
+impl PyRegex {
+    /// Creates a new regular expression by compiling the pattern via Python's `regex.compile`.
+    pub fn new(pattern: &str) -> PyResult<Self> {
+        Self::with_flags(pattern, Flags::NONE)
+    }
+
+    /// Compiles the pattern with the given flags.
+    pub fn with_flags(pattern: &str, flags: Flags) -> PyResult<Self> {
+        Self::compile(pattern, flags, &HashMap::new())
+    }
+
+    /// Returns a builder for patterns that need more than flags, such as named lists.
+    pub fn builder(pattern: &str) -> PyRegexBuilder {
+        PyRegexBuilder::new(pattern)
+    }
+
+    /// Compiles the pattern with the given flags and named lists.
+    pub(crate) fn compile(
+        pattern: &str,
+        flags: Flags,
+        named_lists: &HashMap<String, Vec<String>>,
+    ) -> PyResult<Self> {
+        Python::with_gil(|py| {
+            let module = backend::module(py)?;
+            let mut bits = flags.bits();
+            if backend::active() == Backend::Re {
+                // `re` would silently treat fuzzy constraints as literal text.
+                if !module
+                    .call_method1("search", (FUZZY_SYNTAX, pattern))?
+                    .is_none()
+                {
+                    backend::require(py, Feature::Fuzzy)?;
+                }
+                if flags.contains(Flags::VERSION1) {
+                    backend::require(py, Feature::Version1)?;
+                }
+                // `re` behaves like VERSION0 and ignores flags it does not know instead of
+                // rejecting them.
+                bits &= !Flags::VERSION0.bits();
+                let unsupported = flags.difference(RE_FLAGS | Flags::VERSION0);
+                if unsupported != Flags::NONE {
+                    backend::require(py, Feature::Flag(unsupported))?;
+                }
+                if flags.contains(Flags::ASCII) {
+                    // `re.ASCII` has a different value than `regex.ASCII`.
+                    bits = bits & !Flags::ASCII.bits() | 0x100;
+                }
+            }
+            let kwargs = if named_lists.is_empty() {
+                None
+            } else {
+                backend::require(py, Feature::NamedLists)?;
+                Some(named_lists.into_py_dict(py)?)
+            };
+            let operation = trace::start("compile", || pattern.to_string(), || None);
+            let compiled = operation.finish(module.call_method(
+                "compile",
+                (pattern, bits),
+                kwargs.as_ref(),
+            ))?;
+            Self::from_compiled(compiled)
+        })
+    }
+
+    /// Wraps a compiled Python pattern object.
+    fn from_compiled(compiled: Bound<PyAny>) -> PyResult<Self> {
+        Ok(PyRegex {
+            #[cfg(feature = "metrics")]
+            counters: Counters::register(compiled.getattr("pattern")?.extract()?),
+            compiled: compiled.into(),
+            timeout: None,
+            cancellation: None,
+        })
+    }
+
+    /// Aborts matching operations on this pattern that run longer than `timeout` with a Python
+    /// `TimeoutError`. Returns `UnsupportedFeature` if the installed module has no timeouts.
+    pub fn with_timeout(mut self, timeout: Duration) -> PyResult<Self> {
+        Python::with_gil(|py| backend::require(py, Feature::Timeout))?;
+        self.timeout = Some(timeout);
+        Ok(self)
+    }
+
+    /// Makes batch and streaming operations on this pattern check `token` before each item and
+    /// fail with a `Cancelled` error once it is cancelled. See `CancellationToken`.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Returns a `Cancelled` error if the pattern's cancellation token was cancelled.
+    pub(crate) fn check_cancelled(&self) -> PyResult<()> {
+        self.cancellation
+            .as_ref()
+            .map_or(Ok(()), CancellationToken::check)
+    }
+
+    /// Returns a shared handle to the compiled pattern, compiling it only on first use.
+    /// Subsequent calls with the same pattern return the same handle from a process-wide cache.
+    pub fn cached(pattern: &str) -> PyResult<Arc<Self>> {
+        Self::cached_with_flags(pattern, Flags::NONE)
+    }
+
+    /// Same as `cached`, but keyed by both the pattern and the flags.
+    pub fn cached_with_flags(pattern: &str, flags: Flags) -> PyResult<Arc<Self>> {
+        let cache = CACHE.get_or_init(Default::default);
+        let key = (pattern.to_string(), flags);
+        if let Some(re) = cache.lock().unwrap().get(&key) {
+            return Ok(Arc::clone(re));
+        }
+        // Compile without holding the lock: another thread may hold the GIL while waiting for it.
+        let re = Arc::new(Self::with_flags(pattern, flags)?);
+        Ok(Arc::clone(cache.lock().unwrap().entry(key).or_insert(re)))
+    }
+
+    /// Serializes the compiled pattern with Python's `pickle`, so it can be restored with `load`
+    /// without compiling it again.
+    pub fn dump(&self) -> PyResult<Vec<u8>> {
+        Python::with_gil(|py| {
+            PyModule::import(py, "pickle")?
+                .call_method1("dumps", (&self.compiled,))?
+                .extract::<Vec<u8>>()
+        })
+    }
+
+    /// Restores a pattern serialized by `dump`.
+    /// The data is unpickled, so it must come from a trusted source and the same `regex` version.
+    pub fn load(data: &[u8]) -> PyResult<Self> {
+        Python::with_gil(|py| {
+            Self::from_compiled(
+                PyModule::import(py, "pickle")?.call_method1("loads", (PyBytes::new(py, data),))?,
+            )
+        })
+    }
+
+    /// Returns the underlying Python `Pattern` object, for calling methods the wrapper does not
+    /// expose.
+    pub fn as_py(&self) -> &Py<PyAny> {
+        &self.compiled
+    }
+
+    /// Wraps a `Pattern` object compiled by Python code with the active backend's module.
+    /// Returns a `TypeError` if `obj` is not a compiled pattern.
+    pub fn from_py(obj: Py<PyAny>) -> PyResult<Self> {
+        Python::with_gil(|py| {
+            let obj = obj.into_bound(py);
+            if !obj.hasattr("pattern")? || !obj.hasattr("finditer")? {
+                return Err(PyTypeError::new_err(format!(
+                    "expected a compiled pattern, got {}",
+                    obj.get_type().name()?
+                )));
+            }
+            Self::from_compiled(obj)
+        })
+    }
+
+    /// Returns the source pattern (`Pattern.pattern`).
+    pub fn pattern(&self) -> PyResult<String> {
+        Python::with_gil(|py| Ok(self.source(py)?.0))
+    }
+
+    /// Returns the flags of the compiled pattern (`Pattern.flags`), including those Python sets on
+    /// its own such as `UNICODE`, so they can be modified and passed back to `with_flags`.
+    pub fn flags(&self) -> PyResult<Flags> {
+        Python::with_gil(|py| Ok(self.source(py)?.1))
+    }
+
+    /// Returns the number of capture groups in the pattern (`Pattern.groups`), not counting group 0.
+    pub fn groups_len(&self) -> PyResult<usize> {
+        Python::with_gil(|py| self.compiled.getattr(py, "groups")?.extract::<usize>(py))
+    }
+
+    /// Returns the names of the named groups, ordered by group number.
+    pub fn group_names(&self) -> PyResult<Vec<String>> {
+        Python::with_gil(|py| {
+            let mut names = self.groupindex(py)?.into_iter().collect::<Vec<_>>();
+            names.sort_by_key(|(_, index)| *index);
+            Ok(names.into_iter().map(|(name, _)| name).collect())
+        })
+    }
+
+    /// Returns the mapping from group names to group numbers (`Pattern.groupindex`).
+    pub fn group_index(&self) -> PyResult<HashMap<String, usize>> {
+        Python::with_gil(|py| self.groupindex(py))
+    }
+
+    /// Returns `true` if the pattern has a group with the given name.
+    pub fn has_group(&self, name: &str) -> PyResult<bool> {
+        Python::with_gil(|py| Ok(self.groupindex(py)?.contains_key(name)))
+    }
+
+    /// Reads the source pattern and flags of the compiled pattern.
+    fn source(&self, py: Python) -> PyResult<(String, Flags)> {
+        let pattern = self
+            .compiled
+            .getattr(py, "pattern")?
+            .extract::<String>(py)?;
+        let mut bits = self.compiled.getattr(py, "flags")?.extract::<u32>(py)?;
+        if backend::active() == Backend::Re && bits & 0x100 != 0 {
+            bits = bits & !0x100 | Flags::ASCII.bits();
+        }
+        Ok((pattern, Flags::from_bits(bits)))
+    }
+
+    /// Reads `Pattern.groupindex`.
+    fn groupindex(&self, py: Python) -> PyResult<HashMap<String, usize>> {
+        groupindex(self.compiled.bind(py))
+    }
+
+    /// Starts instrumenting a matching operation on `text`.
+    pub(crate) fn operation<T: PyText + ?Sized>(
+        &self,
+        op: &'static str,
+        text: &T,
+    ) -> trace::Operation {
+        let operation = trace::start(
+            op,
+            || self.pattern().unwrap_or_default(),
+            || Some(text.text_len()),
+        );
+        #[cfg(feature = "metrics")]
+        let operation = operation.counted(&self.counters);
+        operation
+    }
+
+    /// Returns the counters of this handle, shared with its clones.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        self.counters.stats()
+    }
+
+    /// Constructs kwargs with `concurrent=True` where supported and the timeout, or none at all on
+    /// the `re` backend.
+    pub(crate) fn kwargs<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyDict>> {
+        let concurrent = backend::active().supports(Feature::Concurrent);
+        if !concurrent && self.timeout.is_none() {
+            return None;
+        }
+        let kwargs = PyDict::new(py);
+        if concurrent {
+            kwargs.set_item("concurrent", true).ok()?;
+        }
+        if let Some(timeout) = self.timeout {
+            kwargs.set_item("timeout", timeout.as_secs_f64()).ok()?;
+        }
+        Some(kwargs)
+    }
+
+    /// Performs a search for the first match and returns a `PyRegexMatch` object.
+    pub fn search_match<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Option<PyRegexMatch>> {
+        let operation = self.operation("search", text);
+        let result = Python::with_gil(|py| {
+            let result = self.compiled.call_method(
+                py,
+                "search",
+                (text.to_py_str(py),),
+                self.kwargs(py).as_ref(),
+            )?;
+
+            Ok(if result.is_none(py) {
+                None
+            } else {
+                Some(PyRegexMatch::new(result))
+            })
+        });
+        operation.finish_counting(result, |m| m.is_some() as usize)
+    }
+
+    /// Searches for the first match and converts it into `T`, typically a struct deriving
+    /// `FromCaptures` whose fields are filled from the named groups.
+    pub fn captures_as<T: FromCaptures>(
+        &self,
+        text: &(impl PyText + ?Sized),
+    ) -> PyResult<Option<T>> {
+        self.search_match(text)?
+            .map(|m| T::from_captures(&m.to_data()?))
+            .transpose()
+    }
+
+    /// Returns a list of `PyRegexMatch` objects from `finditer()`.
+    pub fn find_iter<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<PyRegexMatch>> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            let mut matches = Vec::new();
+            let binding = self.compiled.call_method(
+                py,
+                "finditer",
+                (text.to_py_str(py),),
+                self.kwargs(py).as_ref(),
+            )?;
+            let iter = binding.downcast_bound::<PyIterator>(py)?;
+            for item in iter {
+                let match_obj = item?;
+                matches.push(PyRegexMatch::new(match_obj.unbind()));
+            }
+            Ok(matches)
+        });
+        operation.finish_counting(result, Vec::len)
+    }
+
+    /// Returns the match at index `n` (0-based) of `find_iter`, without creating the ones before
+    /// it on the Rust side and without searching past it.
+    pub fn find_nth<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+        n: usize,
+    ) -> PyResult<Option<PyRegexMatch>> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            let m = helpers(py)?.call_method1(
+                "find_nth",
+                (self.as_py(), text.to_py_str(py), n, self.kwargs(py)),
+            )?;
+            Ok((!m.is_none()).then(|| PyRegexMatch::new(m.unbind())))
+        });
+        operation.finish_counting(result, |m| m.is_some() as usize)
+    }
+
+    /// Returns the last match of `find_iter`. The matches before it are skipped on the Python
+    /// side; unlike `find_iter_rev`, this finds exactly the matches of a forward search.
+    pub fn find_last<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Option<PyRegexMatch>> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            let m = helpers(py)?.call_method1(
+                "find_last",
+                (self.as_py(), text.to_py_str(py), self.kwargs(py)),
+            )?;
+            Ok((!m.is_none()).then(|| PyRegexMatch::new(m.unbind())))
+        });
+        operation.finish_counting(result, |m| m.is_some() as usize)
+    }
+
+    /// Returns the non-overlapping matches found searching backwards from the end of the text,
+    /// from last to first.
+    ///
+    /// The pattern is recompiled with `Flags::REVERSE`, as for `rsplit`, so where matches overlap
+    /// they may differ from those of `find_iter`. Requires the `regex` backend.
+    pub fn find_iter_rev<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<PyRegexMatch>> {
+        self.find_iter_rev_before(text, isize::MAX as usize)
+    }
+
+    /// Same as `find_iter_rev`, but starting at the code point position `pos`, e.g. an editor's
+    /// cursor: only matches that end at or before it are returned. As with Python's `endpos`, `$`
+    /// also matches at `pos`.
+    pub fn find_iter_rev_before<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+        pos: usize,
+    ) -> PyResult<Vec<PyRegexMatch>> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            self.reversed(py)?
+                .call_method(
+                    "finditer",
+                    (text.to_py_str(py), 0, pos),
+                    self.kwargs(py).as_ref(),
+                )?
+                .try_iter()?
+                .map(|m| Ok(PyRegexMatch::new(m?.unbind())))
+                .collect()
+        });
+        operation.finish_counting(result, Vec::len)
+    }
+
+    /// Returns the `regs` of up to `limit` matches in a single GIL session: the code point spans of
+    /// all groups, with `(-1, -1)` for groups that did not participate.
+    #[cfg(feature = "compat")]
+    pub(crate) fn find_regs(
+        &self,
+        text: &str,
+        limit: Option<usize>,
+    ) -> PyResult<Vec<Vec<(isize, isize)>>> {
+        Python::with_gil(|py| {
+            let binding =
+                self.compiled
+                    .call_method(py, "finditer", (text,), self.kwargs(py).as_ref())?;
+            let iter = binding.downcast_bound::<PyIterator>(py)?.clone();
+            iter.take(limit.unwrap_or(usize::MAX))
+                .map(|item| item?.getattr("regs")?.extract())
+                .collect()
+        })
+    }
+
+    // Other methods remain unchanged.
+    pub fn is_match<T: PyText + ?Sized>(&self, text: &T) -> PyResult<bool> {
+        let operation = self.operation("search", text);
+        let result = Python::with_gil(|py| {
+            Ok(!self
+                .compiled
+                .call_method(
+                    py,
+                    "search",
+                    (text.to_py_str(py),),
+                    self.kwargs(py).as_ref(),
+                )?
+                .is_none(py))
+        });
+        operation.finish_counting(result, |&found| found as usize)
+    }
+
+    /// Returns `true` if the pattern matches at the start of the text (Python's `match`).
+    pub fn starts_with<T: PyText + ?Sized>(&self, text: &T) -> PyResult<bool> {
+        let operation = self.operation("match", text);
+        let result = Python::with_gil(|py| {
+            Ok(!self
+                .compiled
+                .bind(py)
+                .call_method("match", (text.to_py_str(py),), self.kwargs(py).as_ref())?
+                .is_none())
+        });
+        operation.finish_counting(result, |&found| found as usize)
+    }
+
+    /// Returns `true` if the pattern matches at the end of the text, found by matching the
+    /// pattern recompiled with `Flags::REVERSE` backwards from the end. Requires the `regex`
+    /// backend.
+    pub fn ends_with<T: PyText + ?Sized>(&self, text: &T) -> PyResult<bool> {
+        let operation = self.operation("match", text);
+        let result = Python::with_gil(|py| {
+            Ok(!self
+                .reversed(py)?
+                .call_method("match", (text.to_py_str(py),), self.kwargs(py).as_ref())?
+                .is_none())
+        });
+        operation.finish_counting(result, |&found| found as usize)
+    }
+
+    pub fn find_all<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<String>> {
+        let operation = self.operation("findall", text);
+        let result = Python::with_gil(|py| {
+            self.compiled
+                .call_method(
+                    py,
+                    "findall",
+                    (text.to_py_str(py),),
+                    self.kwargs(py).as_ref(),
+                )?
+                .extract::<Vec<String>>(py)
+        });
+        operation.finish(result)
+    }
+
+    /// Returns the distinct matched texts in the order they were first found, e.g. to harvest
+    /// identifiers from a large document. Duplicates are dropped on the Python side.
+    pub fn find_all_unique<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<String>> {
+        self.unique_matches(text, false)
+    }
+
+    /// Same as `find_all_unique`, but matches that only differ in case, as compared by
+    /// `casefold`, count as duplicates; the first spelling found is kept. Returns
+    /// `UnsupportedFeature` on the `re` backend.
+    pub fn find_all_unique_folded<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<String>> {
+        self.unique_matches(text, true)
+    }
+
+    fn unique_matches<T: PyText + ?Sized>(&self, text: &T, fold: bool) -> PyResult<Vec<String>> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            let fold_case = if fold {
+                backend::require(py, Feature::Flag(Flags::FULLCASE))?;
+                Some(py.import("regex._regex")?.getattr("fold_case")?)
+            } else {
+                None
+            };
+            helpers(py)?
+                .call_method1(
+                    "unique_matches",
+                    (
+                        self.as_py(),
+                        text.to_py_str(py),
+                        fold_case,
+                        (Flags::FULLCASE | Flags::IGNORECASE).bits(),
+                        self.kwargs(py),
+                    ),
+                )?
+                .extract::<Vec<String>>()
+        });
+        operation.finish_counting(result, Vec::len)
+    }
+
+    /// Returns the groups of every match, like Python's `findall` for a pattern with several
+    /// groups but with `None` for groups that did not participate instead of an empty string.
+    /// Without groups, each match is returned as a single-element vector with the whole match.
+    pub fn find_all_groups<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+    ) -> PyResult<Vec<Vec<Option<String>>>> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            let compiled = self.compiled.bind(py);
+            let grouped = compiled.getattr("groups")?.extract::<usize>()? > 0;
+            let mut matches = Vec::new();
+            for m in compiled
+                .call_method("finditer", (text.to_py_str(py),), self.kwargs(py).as_ref())?
+                .try_iter()?
+            {
+                let m = m?;
+                matches.push(if grouped {
+                    m.call_method0("groups")?.extract()?
+                } else {
+                    vec![m.call_method0("group")?.extract()?]
+                });
+            }
+            Ok(matches)
+        });
+        operation.finish_counting(result, Vec::len)
+    }
+
+    pub fn replace<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+        replacement: impl AsRef<str>,
+    ) -> PyResult<String> {
+        let operation = self.operation("sub", text);
+        operation.finish(Python::with_gil(|py| {
+            self.compiled
+                .call_method(
+                    py,
+                    "sub",
+                    (replacement.as_ref(), text.to_py_str(py)),
+                    self.kwargs(py).as_ref(),
+                )?
+                .extract::<String>(py)
+        }))
+    }
+
+    /// Keeps only the matched parts of the text, joined by `separator`: the inverse of `remove`.
+    /// The joining happens on the Python side, in the same call as the search.
+    pub fn extract_joined<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+        separator: &str,
+    ) -> PyResult<String> {
+        let operation = self.operation("finditer", text);
+        operation.finish(Python::with_gil(|py| {
+            helpers(py)?
+                .call_method1(
+                    "join_matches",
+                    (self.as_py(), text.to_py_str(py), separator, self.kwargs(py)),
+                )?
+                .extract::<String>()
+        }))
+    }
+
+    /// Deletes every match from the text, like `replace` with an empty replacement but without
+    /// any template to expand.
+    pub fn remove<T: PyText + ?Sized>(&self, text: &T) -> PyResult<String> {
+        Ok(self.remove_count(text)?.0)
+    }
+
+    /// Same as `remove`, also returning the number of deleted matches (Python's `subn`).
+    pub fn remove_count<T: PyText + ?Sized>(&self, text: &T) -> PyResult<(String, usize)> {
+        let operation = self.operation("sub", text);
+        operation.finish(Python::with_gil(|py| {
+            // A replacement without backslashes is used as it is.
+            self.compiled
+                .call_method(
+                    py,
+                    "subn",
+                    ("", text.to_py_str(py)),
+                    self.kwargs(py).as_ref(),
+                )?
+                .extract::<(String, usize)>(py)
+        }))
+    }
+
+    pub fn split<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<String>> {
+        let operation = self.operation("split", text);
+        let result = Python::with_gil(|py| {
+            self.compiled
+                .call_method(py, "split", (text.to_py_str(py),), self.kwargs(py).as_ref())?
+                .extract::<Vec<String>>(py)
+        });
+        operation.finish(result)
+    }
+
+    /// Returns the byte offset and text of every match, like `str::match_indices`, so a pattern
+    /// can replace a substring search without converting positions.
+    ///
+    /// ```
+    /// let re = py_regex::PyRegex::new(r"\d+")?;
+    /// let found = re.match_indices("é1 22")?.collect::<Vec<_>>();
+    /// assert_eq!(found, [(2, "1".to_string()), (4, "22".to_string())]);
+    /// # Ok::<(), pyo3::PyErr>(())
+    /// ```
+    pub fn match_indices<T: PyText + ?Sized>(
+        &self,
+        text: &T,
+    ) -> PyResult<impl Iterator<Item = (usize, String)>> {
+        let operation = self.operation("finditer", text);
+        let result = Python::with_gil(|py| {
+            let text = text.to_py_str(py);
+            let matches = self.compiled.bind(py).call_method(
+                "finditer",
+                (&text,),
+                self.kwargs(py).as_ref(),
+            )?;
+            let text = text.to_cow()?;
+            let offsets = ByteOffsets::new(&text);
+            matches
+                .try_iter()?
+                .map(|m| {
+                    let (start, end) = m?.call_method0("span")?.extract::<(usize, usize)>()?;
+                    let (start, end) = (offsets.get(start), offsets.get(end));
+                    Ok((start, text[start..end].to_string()))
+                })
+                .collect::<PyResult<Vec<_>>>()
+        });
+        Ok(operation.finish_counting(result, Vec::len)?.into_iter())
+    }
+
+    /// Splits the text by the matches found searching backwards from the end, and returns the
+    /// pieces from last to first, like `str::rsplit`. Captured groups are included as in `split`.
+    ///
+    /// The pattern is recompiled with `Flags::REVERSE`, so matches may differ from `split` where
+    /// they overlap, and patterns with named lists are not supported. Requires the `regex` backend.
+    pub fn rsplit<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<String>> {
+        self.rsplit_with(text, 0)
+    }
+
+    /// Same as `rsplit`, but returns at most `n` pieces, like `str::rsplitn`: the last piece is
+    /// the rest of the text, so `rsplit_n(text, 2)?[0]` is the last field without splitting the
+    /// whole text.
+    pub fn rsplit_n<T: PyText + ?Sized>(&self, text: &T, n: usize) -> PyResult<Vec<String>> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        self.rsplit_with(text, n - 1)
+    }
+
+    fn rsplit_with<T: PyText + ?Sized>(&self, text: &T, maxsplit: usize) -> PyResult<Vec<String>> {
+        let operation = self.operation("split", text);
+        let result = Python::with_gil(|py| {
+            let kwargs = self.kwargs(py).unwrap_or_else(|| PyDict::new(py));
+            kwargs.set_item("maxsplit", maxsplit)?;
+            self.reversed(py)?
+                .call_method("split", (text.to_py_str(py),), Some(&kwargs))?
+                .extract::<Vec<String>>()
+        });
+        operation.finish(result)
+    }
+
+    /// Compiles the pattern with `REVERSE` added to its flags. The `regex` module caches the
+    /// result, so repeated calls do not recompile.
+    pub(crate) fn reversed<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        backend::require(py, Feature::Flag(Flags::REVERSE))?;
+        let compiled = self.compiled.bind(py);
+        let flags = compiled.getattr("flags")?.extract::<u32>()? | Flags::REVERSE.bits();
+        backend::module(py)?.call_method1("compile", (compiled.getattr("pattern")?, flags))
+    }
+
+    /// Splits the text by the matches, without the captured groups that `split` includes for
+    /// patterns with groups.
+    pub fn split_discard<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<String>> {
+        self.split_delimited(text, false)
+    }
+
+    /// Splits the text by the matches, with each whole match between the pieces it separates:
+    /// `[piece, delimiter, piece, ...]`, whether or not the pattern has groups.
+    pub fn split_keep<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Vec<String>> {
+        self.split_delimited(text, true)
+    }
+
+    fn split_delimited<T: PyText + ?Sized>(&self, text: &T, keep: bool) -> PyResult<Vec<String>> {
+        let operation = self.operation("split", text);
+        let result = Python::with_gil(|py| {
+            let text = text.to_py_str(py);
+            let matches = self.compiled.bind(py).call_method(
+                "finditer",
+                (&text,),
+                self.kwargs(py).as_ref(),
+            )?;
+            let text = text.to_cow()?;
+            let offsets = ByteOffsets::new(&text);
+            let mut parts = Vec::new();
+            let mut last = 0;
+            for m in matches.try_iter()? {
+                let (start, end) = m?.call_method0("span")?.extract::<(usize, usize)>()?;
+                let (start, end) = (offsets.get(start), offsets.get(end));
+                parts.push(text[last..start].to_string());
+                if keep {
+                    parts.push(text[start..end].to_string());
+                }
+                last = end;
+            }
+            parts.push(text[last..].to_string());
+            Ok(parts)
+        });
+        operation.finish(result)
+    }
+
+    /// Escapes a string.
+    /// The `re` backend only supports `special_only = true` with `literal_spaces = false`.
+    /// `EscapeOptions` does the same with named options.
+    pub fn escape(str: &str, special_only: bool, literal_spaces: bool) -> PyResult<String> {
+        EscapeOptions::new()
+            .special_only(special_only)
+            .literal_spaces(literal_spaces)
+            .escape(str)
+    }
+}
+
+/// Options of `regex.escape`, with the same defaults as in Python.
+///
+/// ```
+/// use py_regex::EscapeOptions;
+///
+/// let escaped = EscapeOptions::new().literal_spaces(true).escape("1 + 1")?;
+/// assert_eq!(escaped, r"1 \+ 1");
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EscapeOptions {
+    special_only: bool,
+    literal_spaces: bool,
+}
+
+impl Default for EscapeOptions {
+    fn default() -> Self {
+        EscapeOptions {
+            special_only: true,
+            literal_spaces: false,
+        }
+    }
+}
+
+impl EscapeOptions {
+    /// Creates the default options, which escape special characters and spaces.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Escapes only characters with a special meaning in patterns (the default) rather than
+    /// every non-alphanumeric character.
+    pub fn special_only(mut self, value: bool) -> Self {
+        self.special_only = value;
+        self
+    }
+
+    /// Leaves spaces unescaped.
+    pub fn literal_spaces(mut self, value: bool) -> Self {
+        self.literal_spaces = value;
+        self
+    }
+
+    /// Escapes a string with these options.
+    /// The `re` backend only supports the default options.
+    pub fn escape(&self, str: &str) -> PyResult<String> {
+        Python::with_gil(|py| {
+            let module = backend::module(py)?;
+            if !backend::active().supports(Feature::EscapeOptions) {
+                if *self != Self::default() {
+                    backend::require(py, Feature::EscapeOptions)?;
+                }
+                return module.call_method1("escape", (str,))?.extract::<String>();
+            }
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("special_only", self.special_only)?;
+            kwargs.set_item("literal_spaces", self.literal_spaces)?;
+            module
+                .call_method("escape", (str,), Some(&kwargs))?
+                .extract::<String>()
+        })
+    }
+}
+
+impl fmt::Debug for PyRegex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match Python::with_gil(|py| self.source(py)) {
+            Ok((pattern, flags)) => f
+                .debug_struct("PyRegex")
+                .field("pattern", &pattern)
+                .field("flags", &flags)
+                .field("timeout", &self.timeout)
+                .finish(),
+            Err(_) => f
+                .debug_struct("PyRegex")
+                .field("compiled", &self.compiled)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl fmt::Display for PyRegex {
+    /// Shows the source pattern, followed by any flags that were set explicitly.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match Python::with_gil(|py| self.source(py)) {
+            Ok((pattern, flags)) => {
+                f.write_str(&pattern)?;
+                let explicit = flags.difference(IMPLICIT_FLAGS);
+                if explicit != Flags::NONE {
+                    write!(f, " ({explicit:?})")?;
+                }
+                Ok(())
+            }
+            Err(_) => write!(f, "{}", self.compiled),
+        }
+    }
+}
+
+impl FromStr for PyRegex {
+    type Err = PyErr;
+
+    /// Compiles the pattern, so `"foo\\d+".parse::<PyRegex>()` works.
+    fn from_str(pattern: &str) -> PyResult<Self> {
+        PyRegex::new(pattern)
+    }
+}
+
+impl TryFrom<&str> for PyRegex {
+    type Error = PyErr;
+
+    fn try_from(pattern: &str) -> PyResult<Self> {
+        PyRegex::new(pattern)
+    }
+}
+
+/// Serializes the pattern text and flags; the timeout is not preserved.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PyRegex {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeStruct};
+
+        let (pattern, flags) = Python::with_gil(|py| self.source(py)).map_err(S::Error::custom)?;
+        let mut state = serializer.serialize_struct("PyRegex", 2)?;
+        state.serialize_field("pattern", &pattern)?;
+        state.serialize_field("flags", &flags)?;
+        state.end()
+    }
+}
+
+/// Deserializes and recompiles either `{ "pattern": ..., "flags": ... }` or a bare pattern string.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PyRegex {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Source {
+            Pattern(String),
+            Full {
+                pattern: String,
+                #[serde(default)]
+                flags: Flags,
+            },
+        }
+
+        let (pattern, flags) = match Source::deserialize(deserializer)? {
+            Source::Pattern(pattern) => (pattern, Flags::NONE),
+            Source::Full { pattern, flags } => (pattern, flags),
+        };
+        PyRegex::with_flags(&pattern, flags).map_err(D::Error::custom)
+    }
+}
+
+/// A wrapper for the match object from the Python `regex` module.
+///
+/// Groups are given as `impl Into<Group>`: a number, 0 for the whole match, or a name. The text
+/// and span of each numbered group are cached after the first access, so calling `group(0)` or
+/// `span(0)` repeatedly only calls into Python once per group.
+pub struct PyRegexMatch {
+    inner: Py<PyAny>,
+    groups: Mutex<HashMap<usize, Option<String>>>,
+    spans: Mutex<HashMap<usize, Option<Range<usize>>>>,
+}
+
+impl PyRegexMatch {
+    /// Wraps a Python `Match` object.
+    pub(crate) fn new(inner: Py<PyAny>) -> Self {
+        PyRegexMatch {
+            inner,
+            groups: Mutex::default(),
+            spans: Mutex::default(),
+        }
+    }
+
+    /// Returns the underlying Python `Match` object.
+    pub fn as_py(&self) -> &Py<PyAny> {
+        &self.inner
+    }
+
+    /// Returns the match for the specified group.
+    /// For example, `group(0)` is the entire match, `group(1)` is the first subgroup, and
+    /// `group("word")` is the group named `word`. Returns an `IndexError` for a missing group.
+    pub fn group<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<Option<String>> {
+        cached(&self.groups, group.into(), |group| {
+            Python::with_gil(|py| {
+                self.inner
+                    .call_method1(py, "group", (group,))?
+                    .extract::<Option<String>>(py)
+            })
+        })
+    }
+
+    /// Returns the specified group parsed with `FromStr`, e.g. `m.group_as::<u32>(2)?`,
+    /// or `None` if the group did not participate.
+    pub fn group_as<'a, T>(&self, group: impl Into<Group<'a>>) -> PyResult<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let group = group.into();
+        self.group(group)?
+            .map(|text| parse_capture(&text, group))
+            .transpose()
+    }
+
+    /// Converts the match into `T` in a single call into Python, typically a tuple of groups 1 to
+    /// N parsed with `FromStr` or a struct deriving `FromCaptures`.
+    ///
+    /// ```
+    /// let re = py_regex::PyRegex::new(r"(\w+)=(\d+)")?;
+    /// let (key, value) = re.search_match("count=7")?.unwrap().extract::<(String, u32)>()?;
+    /// assert_eq!((key.as_str(), value), ("count", 7));
+    /// # Ok::<(), pyo3::PyErr>(())
+    /// ```
+    pub fn extract<T: FromCaptures>(&self) -> PyResult<T> {
+        T::from_captures(&self.to_data()?)
+    }
+
+    /// Returns the named group parsed with `FromStr`, or `None` if it did not participate.
+    #[deprecated(note = "use `group_as`, which accepts group names")]
+    pub fn named_group_as<T>(&self, name: &str) -> PyResult<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.group_as(name)
+    }
+
+    /// Expands a replacement template such as `\1-\g<name>` against this match (`Match.expand`).
+    pub fn expand(&self, template: &str) -> PyResult<String> {
+        Python::with_gil(|py| {
+            self.inner
+                .call_method1(py, "expand", (template,))?
+                .extract::<String>(py)
+        })
+    }
+
+    /// Returns all captured groups as a vector.
+    /// Analogous to Python's `groups()` method, which returns a tuple of all subgroups (starting from 1).
+    pub fn groups(&self) -> PyResult<Vec<Option<String>>> {
+        Python::with_gil(|py| {
+            self.inner
+                .call_method1(py, "groups", ())?
+                .extract::<Vec<Option<String>>>(py)
+        })
+    }
+
+    /// Returns an iterator over groups 1 to N, with `None` for groups that did not participate.
+    /// The groups are fetched with a single call.
+    pub fn iter_groups(&self) -> PyResult<std::vec::IntoIter<Option<String>>> {
+        Ok(self.groups()?.into_iter())
+    }
+
+    /// Returns the named groups dictionary (`groupdict()`) as a `HashMap`.
+    pub fn groupdict(&self) -> PyResult<HashMap<String, Option<String>>> {
+        Python::with_gil(|py| {
+            self.inner
+                .call_method1(py, "groupdict", ())?
+                .extract::<HashMap<String, Option<String>>>(py)
+        })
+    }
+
+    /// Returns the code point span of the specified group, or `None` if the group did not
+    /// participate. `span_bytes` gives the byte range for slicing the haystack.
+    pub fn span<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<Option<Range<usize>>> {
+        cached(&self.spans, group.into(), |group| {
+            let (start, end) = Python::with_gil(|py| {
+                self.inner
+                    .call_method1(py, "span", (group,))?
+                    .extract::<(isize, isize)>(py)
+            })?;
+            Ok(match (usize::try_from(start), usize::try_from(end)) {
+                (Ok(start), Ok(end)) => Some(start..end),
+                _ => None,
+            })
+        })
+    }
+
+    /// Returns the code point span of the named group, or `None` if it did not participate.
+    /// Returns an `IndexError` if the pattern has no such group.
+    #[deprecated(note = "use `span`, which accepts group names")]
+    pub fn span_name(&self, name: &str) -> PyResult<Option<Range<usize>>> {
+        self.span(name)
+    }
+
+    /// Returns the start position of the match for the specified group, or `-1` if the group did
+    /// not participate.
+    #[deprecated(note = "use `span`, which returns `None` instead of -1")]
+    pub fn start<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<isize> {
+        Ok(self.span(group)?.map_or(-1, |span| span.start as isize))
+    }
+
+    /// Returns the end position of the match for the specified group, or `-1` if the group did
+    /// not participate.
+    #[deprecated(note = "use `span`, which returns `None` instead of -1")]
+    pub fn end<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<isize> {
+        Ok(self.span(group)?.map_or(-1, |span| span.end as isize))
+    }
+
+    /// Copies the text and span of every group into an owned `MatchData`, so that `&data[1]` and
+    /// `&data["word"]` work and repeated accesses no longer call into Python.
+    pub fn to_data(&self) -> PyResult<MatchData> {
+        Python::with_gil(|py| {
+            // One call for everything instead of one per field.
+            match_data_from(&helpers(py)?.call_method1("match_data", (&self.inner,))?)
+        })
+    }
+
+    /// Returns the start position of the specified group, or `None` if the group did not
+    /// participate in the match.
+    pub fn start_pos<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<Option<usize>> {
+        Ok(self.span(group)?.map(|span| span.start))
+    }
+
+    /// Returns the end position of the specified group, or `None` if the group did not
+    /// participate in the match.
+    pub fn end_pos<'a>(&self, group: impl Into<Group<'a>>) -> PyResult<Option<usize>> {
+        Ok(self.span(group)?.map(|span| span.end))
+    }
+
+    /// Returns the start of the specified group as a byte offset into `text`, which must be the
+    /// haystack the match was produced from. Returns `None` if the group did not participate.
+    pub fn start_byte<'a>(
+        &self,
+        group: impl Into<Group<'a>>,
+        text: &str,
+    ) -> PyResult<Option<usize>> {
+        Ok(self.span_bytes(group, text)?.map(|span| span.start))
+    }
+
+    /// Returns the end of the specified group as a byte offset into `text`.
+    /// Returns `None` if the group did not participate.
+    pub fn end_byte<'a>(&self, group: impl Into<Group<'a>>, text: &str) -> PyResult<Option<usize>> {
+        Ok(self.span_bytes(group, text)?.map(|span| span.end))
+    }
+
+    /// Returns the byte range of the specified group in `text`, suitable for `&text[range]`.
+    /// Returns `None` if the group did not participate.
+    pub fn span_bytes<'a>(
+        &self,
+        group: impl Into<Group<'a>>,
+        text: &str,
+    ) -> PyResult<Option<Range<usize>>> {
+        let Some(Range { start, end }) = self.span(group)? else {
+            return Ok(None);
+        };
+        let out_of_range = || PyValueError::new_err("match position is out of range for the text");
+        let start_byte = char_to_byte_offset(text, start).ok_or_else(out_of_range)?;
+        let end_byte = start_byte
+            + char_to_byte_offset(&text[start_byte..], end - start).ok_or_else(out_of_range)?;
+        Ok(Some(start_byte..end_byte))
+    }
+}
+
+/// Returns the cached value for a numbered group, or fetches it. Named groups are not cached.
+fn cached<T: Clone>(
+    cache: &Mutex<HashMap<usize, T>>,
+    group: Group,
+    fetch: impl FnOnce(Group) -> PyResult<T>,
+) -> PyResult<T> {
+    let Group::Index(index) = group else {
+        return fetch(group);
+    };
+    if let Some(value) = cache.lock().unwrap().get(&index) {
+        return Ok(value.clone());
+    }
+    let value = fetch(group)?;
+    cache.lock().unwrap().insert(index, value.clone());
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() -> PyResult<()> {
+        // Initialize Python for multithreaded usage.
+        pyo3::prepare_freethreaded_python();
+
+        assert_eq!(PyRegex::escape("[]", false, false)?, "\\[\\]");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_options() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        assert_eq!(EscapeOptions::new().escape("a b.c")?, "a\\ b\\.c");
+        assert_eq!(
+            EscapeOptions::new().literal_spaces(true).escape("a b")?,
+            "a b"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let a = PyRegex::cached(r"\d+")?;
+        let b = PyRegex::cached(r"\d+")?;
+        let c = PyRegex::cached_with_flags(r"\d+", Flags::ASCII)?;
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert!(c.is_match("42")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_str() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let parsed: PyRegex = r"\d+".parse()?;
+        assert!(parsed.is_match("7")?);
+        assert!("(".parse::<PyRegex>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_module_functions() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        assert_eq!(sub(r"\d+", "N", "a1 b22")?, "aN bN");
+        assert_eq!(findall(r"\d+", "a1 b22")?, ["1", "22"]);
+        assert_eq!(
+            search(r"\d+", "a1")?.unwrap().group(0)?.as_deref(),
+            Some("1")
+        );
+        assert!(search(r"\d+", "a")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flag_posix() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let posix = PyRegex::with_flags("a|ab", Flags::POSIX)?;
+        assert_eq!(posix.find_all("ab")?, ["ab"]);
+        assert_eq!(posix.to_string(), "a|ab (POSIX)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flag_word() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        // Default word boundaries do not split "don't" at the apostrophe.
+        assert!(PyRegex::new(r"n\b")?.is_match("don't")?);
+        assert!(!PyRegex::with_flags(r"n\b", Flags::WORD)?.is_match("don't")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flag_fullcase() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        assert!(!PyRegex::with_flags("ss", Flags::IGNORECASE)?.is_match("ß")?);
+        let fullcase = PyRegex::with_flags("ss", Flags::IGNORECASE | Flags::FULLCASE)?;
+        assert!(fullcase.is_match("ß")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flag_bestmatch() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let fuzzy = "(?:cat){e<=1}";
+        let first = PyRegex::new(fuzzy)?.search_match("cut cat")?.unwrap();
+        let best = PyRegex::with_flags(fuzzy, Flags::BESTMATCH)?
+            .search_match("cut cat")?
+            .unwrap();
+        assert_eq!(first.group(0)?.as_deref(), Some("cut"));
+        assert_eq!(best.group(0)?.as_deref(), Some("cat"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flag_version1() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        // Set difference is only available with VERSION1.
+        let consonants = r"[[a-z]--[aeiou]]+";
+        assert!(PyRegex::new(consonants)?.find_all("strength")?.is_empty());
+        let v1 = PyRegex::builder(consonants)
+            .version(crate::Version::V1)
+            .build()?;
+        assert_eq!(v1.find_all("strength")?, ["str", "ngth"]);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_stats() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\d")?;
+        let clone = re.clone();
+        assert_eq!(clone.find_iter("1 2 3")?.len(), 3);
+        assert!(!re.is_match("none")?);
+        let stats = re.stats();
+        assert_eq!((stats.operations, stats.matches, stats.errors), (2, 3, 0));
+        assert!(
+            crate::metrics::snapshot()
+                .iter()
+                .any(|entry| entry.pattern == r"\d" && entry.stats == stats)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_traceback() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let err = PyRegex::new("(").unwrap_err();
+        let message = err.to_string();
+        assert!(message.starts_with("error: missing ) at position 1"));
+        assert!(message.contains("Traceback (most recent call last):"));
+        assert!(message.ends_with("regex._regex_core.error: missing ) at position 1"));
+        Python::with_gil(|py| assert!(err.cause(py).is_some()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_list() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::builder(r"\b\L<fruits>\b")
+            .named_list("fruits", ["apple", "banana"])
+            .build()?;
+        assert_eq!(re.find_all("banana, apples, apple")?, ["banana", "apple"]);
+        assert!(
+            PyRegex::builder("a")
+                .named_list("x", ["b"])
+                .build()
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_display() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::with_flags(r"\w+", Flags::IGNORECASE | Flags::MULTILINE)?;
+        assert_eq!(re.to_string(), r"\w+ (IGNORECASE | MULTILINE)");
+        assert_eq!(PyRegex::new("a")?.to_string(), "a");
+        assert!(format!("{re:?}").contains(r#"pattern: "\\w+""#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern_flags() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::with_flags(r"\w+", Flags::IGNORECASE | Flags::MULTILINE)?;
+        assert_eq!(re.pattern()?, r"\w+");
+        assert!(re.flags()?.contains(Flags::IGNORECASE | Flags::MULTILINE));
+        // Python adds UNICODE on its own, and the flags can be passed back.
+        assert!(re.flags()?.contains(Flags::UNICODE));
+        let copy = PyRegex::with_flags(&re.pattern()?, re.flags()?)?;
+        assert_eq!(copy.flags()?, re.flags()?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn test_py_regex_macro() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re: &'static PyRegex = crate::py_regex!(r"(?P<year>\d{4})-\d{2}");
+        assert!(re.is_match("2024-05")?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn test_derive_from_captures() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        #[derive(crate::FromCaptures)]
+        struct Entry {
+            key: String,
+            #[capture(name = "value")]
+            count: u32,
+            unit: Option<String>,
+        }
+
+        let re = PyRegex::new(r"(?P<key>\w+)=(?P<value>\d+)(?P<unit>[a-z]+)?")?;
+        let entry: Entry = re.captures_as("count=7")?.unwrap();
+        assert_eq!(
+            (entry.key.as_str(), entry.count, entry.unit),
+            ("count", 7, None)
+        );
+        assert!(re.captures_as::<Entry>("count=99999999999").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_load() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::with_flags(r"hello\s+(\w+)", Flags::IGNORECASE)?;
+        let restored = PyRegex::load(&re.dump()?)?;
+        assert_eq!(restored.find_all("HELLO world")?, vec!["world".to_string()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_as_py() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\w+")?;
+        let wrapped = Python::with_gil(|py| {
+            let count = re.as_py().call_method1(py, "subn", ("#", "a b"))?;
+            assert_eq!(count.extract::<(String, usize)>(py)?.1, 2);
+            PyRegex::from_py(re.as_py().clone_ref(py))
+        })?;
+        assert!(wrapped.is_match("a")?);
+        assert!(Python::with_gil(|py| PyRegex::from_py(py.None())).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_names() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(?P<year>\d{4})-(\d{2})-(?P<day>\d{2})")?;
+        assert_eq!(
+            re.group_names()?,
+            vec!["year".to_string(), "day".to_string()]
+        );
+        assert!(re.has_group("day")?);
+        assert!(!re.has_group("month")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_index() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(?P<year>\d{4})-(\d{2})-(?P<day>\d{2})")?;
+        let index = re.group_index()?;
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.get("year"), Some(&1));
+        assert_eq!(index.get("day"), Some(&3));
+        assert!(PyRegex::new(r"(\d)")?.group_index()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_groups_len() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(?P<year>\d{4})-(\d{2})-(?P<day>\d{2})")?;
+        assert_eq!(re.groups_len()?, 3);
+        assert_eq!(PyRegex::new(r"(?:\d)+")?.groups_len()?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_byte_offsets() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let text = "naïve café";
+        let m = PyRegex::new(r"caf(é)")?.search_match(text)?.unwrap();
+        assert_eq!(m.span(0)?, Some(6..10));
+        let span = m.span_bytes(0, text)?.unwrap();
+        assert_eq!(&text[span], "café");
+        assert_eq!(m.start_byte(1, text)?, Some(10));
+        assert_eq!(m.end_byte(1, text)?, Some(text.len()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backend_info() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let info = crate::backend_info()?;
+        assert_eq!(info.backend, Backend::Regex);
+        assert!(info.module_version.is_some());
+        assert!(info.supports(Feature::Timeout));
+        let re = PyRegex::new(r"\d+")?.with_timeout(Duration::from_secs(1))?;
+        assert!(re.is_match("42")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_all_groups() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(\w+)=(\d+)?(;)?")?;
+        assert_eq!(
+            re.find_all_groups("a=1; b=")?,
+            [
+                vec![Some("a".into()), Some("1".into()), Some(";".into())],
+                vec![Some("b".into()), None, None],
+            ]
+        );
+        assert_eq!(
+            PyRegex::new(r"\d")?.find_all_groups("1a2")?,
+            [[Some("1".to_string())], [Some("2".to_string())]]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_keep_discard() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\s*([,;])|(!)")?;
+        assert_eq!(re.split_discard("é , b;c")?, ["é", " b", "c"]);
+        assert_eq!(
+            re.split_keep("é , b;c!")?,
+            ["é", " ,", " b", ";", "c", "!", ""]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_starts_ends_with() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\d+|ab")?;
+        assert!(re.starts_with("12é")?);
+        assert!(!re.starts_with("é12")?);
+        assert!(re.ends_with("é12")?);
+        assert!(re.ends_with("xab")?);
+        assert!(!re.ends_with("12é")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_all_unique() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\w+")?;
+        let text = "Straße b STRASSE a b straße";
+        assert_eq!(
+            re.find_all_unique(text)?,
+            ["Straße", "b", "STRASSE", "a", "straße"]
+        );
+        assert_eq!(re.find_all_unique_folded(text)?, ["Straße", "b", "a"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_extract() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\s*\([^)]*\)")?;
+        assert_eq!(re.remove("Rust (lang) é (x)!")?, "Rust é!");
+        assert_eq!(re.remove_count("a (1) (2)")?, ("a".to_string(), 2));
+        assert_eq!(re.remove_count("none")?, ("none".to_string(), 0));
+        assert_eq!(
+            re.extract_joined("Rust (lang) é (x)!", "|")?,
+            " (lang)| (x)"
+        );
+        assert_eq!(re.extract_joined("none", "|")?, "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_nth_last() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\d+")?;
+        let group = |m: Option<PyRegexMatch>| m.map(|m| m.group(0)).transpose();
+        assert_eq!(
+            group(re.find_nth("a1 é22 333", 1)?)?.flatten().as_deref(),
+            Some("22")
+        );
+        assert!(re.find_nth("a1 é22 333", 3)?.is_none());
+        let last = re.find_last("a1 é22 333")?.unwrap();
+        assert_eq!(last.span(0)?, Some(7..10));
+        assert!(re.find_last("abc")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reverse() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"/")?;
+        assert_eq!(re.rsplit("usr/lib/é")?, ["é", "lib", "usr"]);
+        assert_eq!(re.rsplit_n("usr/lib/é", 2)?, ["é", "usr/lib"]);
+        assert!(re.rsplit_n("usr/lib/é", 0)?.is_empty());
+        assert_eq!(
+            PyRegex::new(r"(=)")?.rsplit_n("a=b=c", 2)?,
+            ["c", "=", "a=b"]
+        );
+
+        let re = PyRegex::new(r"\w+")?;
+        let words = |matches: Vec<PyRegexMatch>| -> PyResult<Vec<String>> {
+            matches.iter().map(|m| Ok(m.group(0)?.unwrap())).collect()
+        };
+        assert_eq!(words(re.find_iter_rev("ab é cd")?)?, ["cd", "é", "ab"]);
+        assert_eq!(
+            words(re.find_iter_rev_before("ab é cd", 6)?)?,
+            ["c", "é", "ab"]
+        );
+        assert_eq!(re.find_iter_rev("ab é cd")?[1].span(0)?, Some(3..4));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "pypy")]
+    #[test]
+    fn test_pypy_kwargs() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"\d+")?.with_timeout(Duration::from_secs(1))?;
+        Python::with_gil(|py| {
+            let kwargs = re.kwargs(py).expect("timeout kwargs");
+            assert!(!kwargs.contains("concurrent")?);
+            assert!(kwargs.contains("timeout")?);
+            Ok::<_, PyErr>(())
+        })?;
+        assert_eq!(re.find_all("a1 b22")?, ["1", "22"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_casefold() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        assert_eq!(casefold("Straße ǅ ﬁ")?, "strasse ǆ fi");
+        let re = PyRegex::with_flags("STRASSE", Flags::IGNORECASE | Flags::FULLCASE)?;
+        assert!(re.is_match(&casefold("Straße")?)?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::with_flags(r"ab+", Flags::IGNORECASE)?;
+        let json = serde_json::to_string(&re).unwrap();
+        let restored: PyRegex = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_match("ABB")?);
+        let bare: PyRegex = serde_json::from_str(r#""\\d+""#).unwrap();
+        assert!(bare.is_match("7")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_number_or_name() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let m = PyRegex::new(r"(?P<word>\w+)-(\d+)")?
+            .search_match("Test-123")?
+            .unwrap();
+        let (word, number): (u16, u32) = (1, 2);
+        assert_eq!(m.group(word)?.as_deref(), Some("Test"));
+        assert_eq!(m.span(number)?, Some(5..8));
+        assert_eq!(m.group_as::<u32>(2u8)?, Some(123));
+        assert!(m.group(-1).is_err());
+        assert_eq!(m.group("word")?.as_deref(), Some("Test"));
+        assert!(m.group_as::<u32>("word").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pyregex_match_methods() -> PyResult<()> {
+        // Initialize Python for multithreaded usage.
+        pyo3::prepare_freethreaded_python();
+
+        // Use a pattern with a named group and multiple subgroups.
+        let pattern = r"(?P<word>\w+)-(\d+)";
+        let text = "Test-123";
+        let re = PyRegex::new(pattern)?;
+
+        if let Some(m) = re.search_match(text)? {
+            // Check the full match via group(0)
+            assert_eq!(m.group(0)?, Some("Test-123".to_string()));
+
+            // First subgroup (without a name)
+            assert_eq!(m.group(1)?, Some("Test".to_string()));
+
+            // Second subgroup (the number)
+            assert_eq!(m.group(2)?, Some("123".to_string()));
+
+            // Get the named groups dictionary
+            let gd = m.groupdict()?;
+            assert_eq!(gd.get("word").cloned(), Some(Some("Test".to_string())));
+
+            // Get the match span for group 0
+            assert_eq!(m.span(0)?, Some(0..8));
+        } else {
+            panic!("No match found");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unmatched_group_positions() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let m = PyRegex::new(r"(a)|(b)")?.search_match("b")?.unwrap();
+        assert_eq!(m.start_pos(1)?, None);
+        assert_eq!(m.end_pos(1)?, None);
+        assert_eq!(m.start_pos(2)?, Some(0));
+        assert_eq!(m.end_pos(2)?, Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_data_index() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let data = PyRegex::new(r"(?P<word>\w+)-(\d+)")?
+            .search_match("Test-123")?
+            .unwrap()
+            .to_data()?;
+        assert_eq!(&data[0], "Test-123");
+        assert_eq!(&data[2], "123");
+        assert_eq!(&data["word"], "Test");
+        assert_eq!(data.span(0), Some(0..8));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_as() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let m = PyRegex::new(r"(\w+)-(\d+)(!)?")?
+            .search_match("Test-300")?
+            .unwrap();
+        assert_eq!(m.group_as::<u32>(2)?, Some(300));
+        assert_eq!(m.group_as::<u8>(3)?, None);
+        assert!(m.group_as::<u32>(1).is_err());
+        assert!(m.group_as::<u8>(2).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let m = PyRegex::new(r"(?P<word>\w+)-(\d+)")?
+            .search_match("Test-123")?
+            .unwrap();
+        assert_eq!(m.expand(r"\2:\g<word>")?, "123:Test");
+        assert!(m.expand(r"\3").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iter_groups() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let m = PyRegex::new(r"(a)|(b)")?.search_match("b")?.unwrap();
+        assert_eq!(
+            m.iter_groups()?.collect::<Vec<_>>(),
+            [None, Some("b".to_string())]
+        );
+        let data = PyRegex::new(r"(\w+)-(\d+)")?
+            .search_match("Test-123")?
+            .unwrap()
+            .to_data()?;
+        assert_eq!(
+            data.iter_groups().collect::<Vec<_>>(),
+            [Some("Test"), Some("123")]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_span() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let m = PyRegex::new(r"é(\d+)(x)?")?
+            .search_match("café42")?
+            .unwrap();
+        assert_eq!(m.span(0)?, Some(3..6));
+        assert_eq!(m.span(1)?, Some(4..6));
+        assert_eq!(m.span(2)?, None);
+        assert!(m.span(3).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_span_by_name() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let m = PyRegex::new(r"(?P<word>\w+)-(?P<number>\d+)?")?
+            .search_match("Test-")?
+            .unwrap();
+        assert_eq!(m.span("word")?, Some(0..4));
+        assert_eq!(m.span("number")?, None);
+        assert!(m.span("missing").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_cache() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let m = PyRegex::new(r"(\w+)-(\d+)|(x)")?
+            .search_match("Test-123")?
+            .unwrap();
+        assert!(m.groups.lock().unwrap().is_empty());
+        assert_eq!(m.group(1)?.as_deref(), Some("Test"));
+        assert_eq!(m.group(3)?, None);
+        assert_eq!(m.span(3)?, None);
+        assert_eq!(m.groups.lock().unwrap().len(), 2);
+        assert_eq!(m.spans.lock().unwrap().get(&3), Some(&None));
+        // Repeated accesses are served from the cache.
+        assert_eq!(m.group(1)?.as_deref(), Some("Test"));
+        assert_eq!(m.span(3)?, None);
+        assert_eq!(m.groups.lock().unwrap().len(), 2);
+        assert_eq!(m.spans.lock().unwrap().len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use crate::cancel::CancellationToken;
+use crate::flags::Flags;
+use crate::python::PyRegex;
+use pyo3::PyResult;
+use std::io::{self, BufRead};
+
+/// Groups lines into multi-line records, each starting at a line that matches a "record start"
+/// pattern, e.g. log entries together with the stack traces that follow them.
+///
+/// ```
+/// use py_regex::RecordSplitter;
+///
+/// let log = "2024-01-01 ERROR boom\n  at a.b(C.java:1)\n2024-01-01 INFO ok\n";
+/// let splitter = RecordSplitter::new(r"^\d{4}-\d{2}-\d{2} ")?;
+/// let records = splitter.records(log.as_bytes()).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(records, ["2024-01-01 ERROR boom\n  at a.b(C.java:1)", "2024-01-01 INFO ok"]);
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordSplitter {
+    start: PyRegex,
+}
+
+impl RecordSplitter {
+    /// Compiles the pattern that recognizes the first line of a record. It is searched for
+    /// anywhere in the line, so it usually starts with `^`.
+    pub fn new(start: &str) -> PyResult<Self> {
+        Self::with_flags(start, Flags::NONE)
+    }
+
+    /// Compiles the record start pattern with the given flags.
+    pub fn with_flags(start: &str, flags: Flags) -> PyResult<Self> {
+        Ok(RecordSplitter {
+            start: PyRegex::with_flags(start, flags)?,
+        })
+    }
+
+    /// Stops reading records with a `Cancelled` error once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.start = self.start.with_cancellation(token);
+        self
+    }
+
+    /// Returns the records of the reader's lines.
+    pub fn records<R: BufRead>(&self, reader: R) -> Records<io::Lines<R>> {
+        self.records_from_lines(reader.lines())
+    }
+
+    /// Returns the records of the lines, which must not include their line terminators.
+    pub fn records_from_lines<I>(&self, lines: I) -> Records<I::IntoIter>
+    where
+        I: IntoIterator<Item = io::Result<String>>,
+    {
+        Records {
+            start: self.start.clone(),
+            lines: lines.into_iter(),
+            current: None,
+        }
+    }
+}
+
+/// An iterator over records, created by `RecordSplitter::records`.
+///
+/// Yields each record once the line starting the next one (or the end of the input) is read, with
+/// its lines joined by `\n`. Lines before the first record start form a record of their own.
+#[derive(Debug)]
+pub struct Records<I> {
+    start: PyRegex,
+    lines: I,
+    current: Option<String>,
+}
+
+impl<I: Iterator<Item = io::Result<String>>> Iterator for Records<I> {
+    type Item = PyResult<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(line) = self.lines.next() else {
+                return self.current.take().map(Ok);
+            };
+            if let Err(err) = self.start.check_cancelled() {
+                return Some(Err(err));
+            }
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            let starts_record = match self.start.is_match(&line) {
+                Ok(starts_record) => starts_record,
+                Err(err) => return Some(Err(err)),
+            };
+            match &mut self.current {
+                Some(record) if !starts_record => {
+                    record.push('\n');
+                    record.push_str(&line);
+                }
+                current => {
+                    if let Some(record) = current.replace(line) {
+                        return Some(Ok(record));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let log = "\
+            preamble\n\
+            2024-01-01 ERROR boom\n\
+            \tat a.b(C.java:1)\n\
+            \tat c.d(E.java:2)\n\
+            2024-01-02 INFO ok\n";
+        let splitter = RecordSplitter::new(r"^\d{4}-\d{2}-\d{2} ")?;
+        let records = splitter
+            .records(log.as_bytes())
+            .collect::<PyResult<Vec<_>>>()?;
+        assert_eq!(
+            records,
+            [
+                "preamble",
+                "2024-01-01 ERROR boom\n\tat a.b(C.java:1)\n\tat c.d(E.java:2)",
+                "2024-01-02 INFO ok"
+            ]
+        );
+        assert_eq!(splitter.records(&b""[..]).count(), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+use crate::flags::Flags;
+use crate::haystack::PyText;
+use crate::offsets::ByteOffsets;
+use crate::python::PyRegex;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::fmt;
+use std::ops::Range;
+
+/// How a `Redactor` rewrites the text matched by a rule.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Policy {
+    /// Replaces every character with the given one.
+    Mask(char),
+    /// Replaces the text with the 64 hex digits of its HMAC-SHA256 keyed with the given secret,
+    /// so equal values stay recognizable as equal. Without the secret, a short value such as an
+    /// ID cannot be recovered by hashing every candidate, so keep it out of the redacted output
+    /// and use the same one wherever hashes are compared. See `Policy::hash`.
+    Hash(Vec<u8>),
+    /// Masks every character with `*` except the last ones, e.g. `KeepLast(4)` for card numbers.
+    KeepLast(usize),
+    /// Removes the text.
+    Drop,
+}
+
+impl Policy {
+    /// Returns a `Policy::Hash` keyed with `secret`, which must not be empty.
+    pub fn hash(secret: impl AsRef<[u8]>) -> Self {
+        Policy::Hash(secret.as_ref().to_vec())
+    }
+
+    fn apply(&self, py: Python<'_>, text: &str) -> PyResult<String> {
+        Ok(match self {
+            Policy::Mask(mask) => text.chars().map(|_| *mask).collect(),
+            Policy::Hash(secret) => py
+                .import("hmac")?
+                .call_method1("new", (secret.as_slice(), text.as_bytes(), "sha256"))?
+                .call_method0("hexdigest")?
+                .extract()?,
+            Policy::KeepLast(keep) => {
+                let masked = text.chars().count().saturating_sub(*keep);
+                text.chars()
+                    .enumerate()
+                    .map(|(index, c)| if index < masked { '*' } else { c })
+                    .collect()
+            }
+            Policy::Drop => String::new(),
+        })
+    }
+}
+
+/// Leaves out the secret of `Policy::Hash`.
+impl fmt::Debug for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Policy::Mask(mask) => f.debug_tuple("Mask").field(mask).finish(),
+            Policy::Hash(_) => f.write_str("Hash(..)"),
+            Policy::KeepLast(keep) => f.debug_tuple("KeepLast").field(keep).finish(),
+            Policy::Drop => f.write_str("Drop"),
+        }
+    }
+}
+
+/// One redacted occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redaction {
+    /// The index of the rule that matched.
+    pub rule: usize,
+    /// The code point span of the occurrence in the original text.
+    pub span: Range<usize>,
+}
+
+/// The result of `Redactor::redact`: the redacted text and what was redacted where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redacted {
+    /// The text with every occurrence rewritten by its rule's policy.
+    pub text: String,
+    /// The occurrences, in order.
+    pub redactions: Vec<Redaction>,
+}
+
+/// Scrubs text with several patterns, each with its own `Policy`, in a single pass.
+///
+/// The rules are compiled into one alternation, so at each position the first rule that matches
+/// wins. Patterns should not contain numbered backreferences, which would refer to the wrong
+/// groups in the combined pattern.
+///
+/// ```
+/// use py_regex::{Policy, Redactor};
+///
+/// let redactor = Redactor::new([
+///     (r"\b\d{4}(?: ?\d{4}){3}\b", Policy::KeepLast(4)),
+///     (r"[\w.]+@[\w.]+", Policy::Mask('#')),
+/// ])?;
+/// let redacted = redactor.redact("card 4111 1111 1111 1234, mail a@b.c")?;
+/// assert_eq!(redacted.text, "card ***************1234, mail #####");
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    re: PyRegex,
+    policies: Vec<Policy>,
+}
+
+impl Redactor {
+    /// Compiles the `(pattern, policy)` rules, in priority order.
+    pub fn new<I, P>(rules: I) -> PyResult<Self>
+    where
+        I: IntoIterator<Item = (P, Policy)>,
+        P: AsRef<str>,
+    {
+        Self::with_flags(rules, Flags::NONE)
+    }
+
+    /// Compiles the `(pattern, policy)` rules with the given flags. Returns a `ValueError` if
+    /// there are no rules or a `Policy::Hash` has an empty secret.
+    pub fn with_flags<I, P>(rules: I, flags: Flags) -> PyResult<Self>
+    where
+        I: IntoIterator<Item = (P, Policy)>,
+        P: AsRef<str>,
+    {
+        let (alternatives, policies): (Vec<_>, Vec<_>) = rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, (pattern, policy))| {
+                (format!("(?P<_rule{index}>{})", pattern.as_ref()), policy)
+            })
+            .unzip();
+        if alternatives.is_empty() {
+            return Err(PyValueError::new_err("a redactor needs at least one rule"));
+        }
+        if policies.contains(&Policy::Hash(Vec::new())) {
+            return Err(PyValueError::new_err(
+                "Policy::Hash needs a non-empty secret",
+            ));
+        }
+        Ok(Redactor {
+            re: PyRegex::with_flags(&alternatives.join("|"), flags)?,
+            policies,
+        })
+    }
+
+    /// Applies the rules to the text.
+    pub fn redact<T: PyText + ?Sized>(&self, text: &T) -> PyResult<Redacted> {
+        Python::with_gil(|py| {
+            let text = text.to_py_str(py);
+            let matches = self.re.as_py().bind(py).call_method(
+                "finditer",
+                (&text,),
+                self.re.kwargs(py).as_ref(),
+            )?;
+            let text = text.to_cow()?;
+            let offsets = ByteOffsets::new(&text);
+            let mut redacted = String::with_capacity(text.len());
+            let mut redactions = Vec::new();
+            let mut copied = 0;
+            for m in matches.try_iter()? {
+                let m = m?;
+                let (start, end) = m.call_method0("span")?.extract::<(usize, usize)>()?;
+                let rule = m
+                    .getattr("lastgroup")?
+                    .extract::<String>()?
+                    .trim_start_matches("_rule")
+                    .parse::<usize>()?;
+                let (start_byte, end_byte) = (offsets.get(start), offsets.get(end));
+                redacted.push_str(&text[copied..start_byte]);
+                redacted.push_str(&self.policies[rule].apply(py, &text[start_byte..end_byte])?);
+                copied = end_byte;
+                redactions.push(Redaction {
+                    rule,
+                    span: start..end,
+                });
+            }
+            redacted.push_str(&text[copied..]);
+            Ok(Redacted {
+                text: redacted,
+                redactions,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let redactor = Redactor::new([
+            (r"\b\d{4}(?: ?\d{4}){3}\b", Policy::KeepLast(4)),
+            (r"[\w.]+@[\w.]+", Policy::Mask('#')),
+            (r"token=\w+", Policy::Drop),
+            (r"\bid\d+", Policy::hash("secret")),
+        ])?;
+        let redacted = redactor.redact("é 4111 1111 1111 1234, a@b.c token=x id7 id7")?;
+        let hash = "e687a0c7f4b4d3e078dae3d7b32d520db749c93e7ae1e540d915ee15d2d2bd26";
+        assert_eq!(
+            redacted.text,
+            format!("é ***************1234, #####  {hash} {hash}")
+        );
+        assert_eq!(
+            redacted.redactions[0],
+            Redaction {
+                rule: 0,
+                span: 2..21
+            }
+        );
+        assert_eq!(
+            redacted
+                .redactions
+                .iter()
+                .map(|redaction| redaction.rule)
+                .collect::<Vec<_>>(),
+            [0, 1, 2, 3, 3]
+        );
+
+        let other = Redactor::new([(r"\bid\d+", Policy::hash("other"))])?;
+        assert_ne!(other.redact("id7")?.text, hash);
+        assert!(Redactor::new([("a", Policy::hash(""))]).is_err());
+        assert!(!format!("{redactor:?}").contains("secret"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::examples::GENERATOR;
+use crate::helpers::HELPERS;
+use crate::python::purge;
+use pyo3::prelude::*;
+
+/// Drops the Python objects that the crate caches: compiled patterns behind `PyRegex::cached`,
+/// the `regex` module's own pattern cache and the embedded helper modules, then runs the
+/// garbage collector. Everything is recreated on next use.
+///
+/// Call it before an embedding application finalizes Python, or between test suites and plugin
+/// reloads that should not see objects created by earlier ones. The interpreter itself keeps
+/// running: PyO3 cannot initialize it again after `Py_Finalize`, so `PyRegex` handles that are
+/// still held remain valid. Does nothing if the interpreter was never initialized.
+pub fn shutdown() -> PyResult<()> {
+    if unsafe { pyo3::ffi::Py_IsInitialized() } == 0 {
+        return Ok(());
+    }
+    Python::with_gil(|py| {
+        drop((HELPERS.clear(), GENERATOR.clear()));
+        purge()?;
+        py.import("gc")?.call_method0("collect")?;
+        Ok(())
+    })
+}
+
+/// Initializes the interpreter when created and calls `shutdown` when dropped, so that cached
+/// Python objects do not outlive the scope that uses the crate.
+///
+/// ```
+/// {
+///     let _runtime = py_regex::Runtime::new();
+///     assert!(py_regex::PyRegex::cached(r"\d+")?.is_match("7")?);
+/// } // The cached pattern is dropped here.
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[must_use = "the cached objects are dropped as soon as the runtime is"]
+#[derive(Debug)]
+pub struct Runtime(());
+
+impl Runtime {
+    /// Initializes the interpreter if needed.
+    pub fn new() -> Self {
+        // PyPy cannot be embedded, so its interpreter is already running.
+        #[cfg(not(feature = "pypy"))]
+        pyo3::prepare_freethreaded_python();
+        Runtime(())
+    }
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        if let Err(err) = shutdown() {
+            Python::with_gil(|py| err.print(py));
+        }
+    }
+}
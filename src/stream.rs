@@ -0,0 +1,638 @@
+//! Matching over input that is read incrementally.
+//!
+//! Deciding whether a match is complete needs partial matching (`partial=True`) to tell a match
+//! that more input could extend or move from a final one, so these APIs require the `regex`
+//! backend.
+
+use crate::backend::{self, Feature};
+use crate::offsets::ByteOffsets;
+use crate::python::{PyRegex, PyRegexMatch};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::io::{self, BufRead, Write};
+use std::mem;
+use std::ops::Range;
+use std::str::Utf8Error;
+
+/// Text read from a `BufRead` and decoded as UTF-8, chunk by chunk.
+pub(crate) struct Decoder<R> {
+    reader: R,
+    /// Bytes of a UTF-8 sequence split across chunks.
+    pending: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: BufRead> Decoder<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Decoder {
+            reader,
+            pending: Vec::new(),
+            eof: false,
+        }
+    }
+
+    /// Returns `true` once the reader is exhausted.
+    pub(crate) fn eof(&self) -> bool {
+        self.eof
+    }
+
+    /// Appends the next chunk to `text`, or sets `eof`.
+    pub(crate) fn read_into(&mut self, text: &mut String) -> io::Result<()> {
+        let chunk = self.reader.fill_buf()?;
+        if chunk.is_empty() {
+            self.eof = true;
+            if !self.pending.is_empty() {
+                return Err(invalid_utf8());
+            }
+            return Ok(());
+        }
+        let read = chunk.len();
+        let decoded = decode_into(&mut self.pending, chunk, text);
+        self.reader.consume(read);
+        decoded.map_err(|_| invalid_utf8())
+    }
+}
+
+/// Appends the complete UTF-8 sequences of `pending` followed by `chunk` to `text`, keeping the
+/// bytes of a trailing incomplete sequence in `pending`.
+fn decode_into(pending: &mut Vec<u8>, chunk: &[u8], text: &mut String) -> Result<(), Utf8Error> {
+    pending.extend_from_slice(chunk);
+    let valid = match std::str::from_utf8(pending) {
+        Ok(valid) => valid.len(),
+        Err(err) if err.error_len().is_none() => err.valid_up_to(),
+        Err(err) => {
+            pending.clear();
+            return Err(err);
+        }
+    };
+    text.push_str(std::str::from_utf8(&pending[..valid]).expect("validated above"));
+    pending.drain(..valid);
+    Ok(())
+}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream did not contain valid UTF-8",
+    )
+}
+
+/// How many characters before the search position a stream keeps, so that lookbehinds, `\b` and
+/// anchors see the text before it as they would in the whole text. Lookbehinds reaching further
+/// back see a truncated text.
+pub(crate) const CONTEXT: usize = 256;
+
+/// Decoded text of a stream, of which the part before `scan` has been processed and is only kept
+/// as context for the searches that follow.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Window {
+    text: String,
+    /// Byte position in `text` where the unprocessed text starts.
+    scan: usize,
+}
+
+impl Window {
+    /// Returns the buffered text, with up to `CONTEXT` characters of processed text.
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the text for appending decoded input.
+    pub(crate) fn text_mut(&mut self) -> &mut String {
+        &mut self.text
+    }
+
+    /// Returns the byte position in `text` where the unprocessed text starts.
+    pub(crate) fn scan(&self) -> usize {
+        self.scan
+    }
+
+    /// Returns the text that has not been processed yet.
+    pub(crate) fn unprocessed(&self) -> &str {
+        &self.text[self.scan..]
+    }
+
+    /// Marks the text before the byte position `to` as processed and drops all but its last
+    /// `CONTEXT` characters. Returns the number of bytes dropped.
+    pub(crate) fn advance(&mut self, to: usize) -> usize {
+        let dropped = self.text[..to]
+            .char_indices()
+            .rev()
+            .nth(CONTEXT - 1)
+            .map_or(0, |(index, _)| index);
+        self.text.drain(..dropped);
+        self.scan = to - dropped;
+        dropped
+    }
+
+    /// Forgets all text.
+    pub(crate) fn clear(&mut self) {
+        self.text.clear();
+        self.scan = 0;
+    }
+
+    /// Searches the unprocessed text, with the processed text as context.
+    pub(crate) fn search(&self, re: &PyRegex, eof: bool) -> PyResult<Found> {
+        search_final(re, &self.text, self.scan, eof)
+    }
+}
+
+/// The outcome of searching buffered text that may continue.
+pub(crate) enum Found {
+    /// A final match and its byte range in the text.
+    Match(Range<usize>, PyRegexMatch),
+    /// No final match yet. More input is needed, and no match can start before the given byte
+    /// position.
+    NeedMore(usize),
+    /// The text is complete and there is no match.
+    None,
+}
+
+/// Searches `text` from the byte position `pos` for a match that more input cannot change. The
+/// text before `pos` is seen by lookbehinds, `\b` and anchors but not searched. Empty matches
+/// are skipped, but as with `finditer`, a non-empty match may still start where one ended.
+pub(crate) fn search_final(re: &PyRegex, text: &str, pos: usize, eof: bool) -> PyResult<Found> {
+    re.check_cancelled()?;
+    let offsets = ByteOffsets::new(text);
+    let pos = text[..pos].chars().count();
+    Python::with_gil(|py| {
+        backend::require(py, Feature::Partial)?;
+        let kwargs = re.kwargs(py).unwrap_or_else(|| PyDict::new(py));
+        kwargs.set_item("partial", !eof)?;
+        let matches = re
+            .as_py()
+            .bind(py)
+            .call_method("finditer", (text, pos), Some(&kwargs))?;
+        let len = text.chars().count();
+        for m in matches.try_iter()? {
+            let m = m?;
+            let (start, end) = m.call_method0("span")?.extract::<(usize, usize)>()?;
+            if !eof && (m.getattr("partial")?.is_truthy()? || end == len) {
+                return Ok(Found::NeedMore(offsets.get(start)));
+            }
+            if start != end {
+                let range = offsets.get(start)..offsets.get(end);
+                return Ok(Found::Match(range, PyRegexMatch::new(m.unbind())));
+            }
+        }
+        Ok(if eof {
+            Found::None
+        } else {
+            Found::NeedMore(text.len())
+        })
+    })
+}
+
+impl PyRegex {
+    /// Splits the reader's text on the matches of the pattern, reading only as much as needed
+    /// to find the next delimiter, including delimiters that span chunk boundaries.
+    ///
+    /// Unlike `split`, captured groups are not included in the output and empty matches do not
+    /// split. The 256 characters before the next delimiter search are kept as context, so `\b`,
+    /// anchors and lookbehinds split as `split` does on the whole text, whatever the reader's
+    /// buffer size. Requires the `regex` backend; Python errors are reported as `io::Error`s.
+    ///
+    /// ```no_run
+    /// let re = py_regex::PyRegex::new(r"\r?\n---\r?\n")?;
+    /// let input = std::io::BufReader::new(std::fs::File::open("documents.txt")?);
+    /// for document in re.split_stream(input) {
+    ///     println!("{} bytes", document?.len());
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn split_stream<R: BufRead>(&self, reader: R) -> SplitStream<R> {
+        SplitStream {
+            re: self.clone(),
+            input: Decoder::new(reader),
+            window: Window::default(),
+            piece: String::new(),
+            done: false,
+        }
+    }
+}
+
+impl PyRegex {
+    /// Copies the reader's text to the writer with every match replaced, as `replace` would,
+    /// holding only as much text as a match could still extend, so files of any size are
+    /// rewritten in constant memory (unless a single match is unbounded). Returns the number of
+    /// replacements.
+    ///
+    /// Text before each match is written out, keeping its last 256 characters as context, so
+    /// `\b`, anchors and lookbehinds match as `replace` does on the whole text, whatever the
+    /// reader's buffer size (unless a lookbehind reaches further back). Empty matches are not
+    /// replaced.
+    /// Requires the `regex` backend; Python errors are reported as `io::Error`s.
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use std::io::{BufReader, BufWriter};
+    ///
+    /// let re = py_regex::PyRegex::new(r"(?P<user>\w+)@example\.org")?;
+    /// let input = BufReader::new(File::open("dump.sql")?);
+    /// let output = BufWriter::new(File::create("dump.redacted.sql")?);
+    /// let replaced = re.replace_stream(input, output, r"\g<user>@example.invalid")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn replace_stream<R: BufRead, W: Write>(
+        &self,
+        reader: R,
+        mut writer: W,
+        replacement: impl AsRef<str>,
+    ) -> io::Result<usize> {
+        let replacement = replacement.as_ref();
+        let mut input = Decoder::new(reader);
+        let mut window = Window::default();
+        let mut replaced = 0;
+        loop {
+            let found = window.search(self, input.eof());
+            match found.map_err(io::Error::other)? {
+                Found::Match(range, m) => {
+                    writer.write_all(&window.text().as_bytes()[window.scan()..range.start])?;
+                    let expanded = m.expand(replacement).map_err(io::Error::other)?;
+                    writer.write_all(expanded.as_bytes())?;
+                    window.advance(range.end);
+                    replaced += 1;
+                }
+                Found::NeedMore(start) => {
+                    writer.write_all(&window.text().as_bytes()[window.scan()..start])?;
+                    window.advance(start);
+                    input.read_into(window.text_mut())?;
+                }
+                Found::None => {
+                    writer.write_all(window.unprocessed().as_bytes())?;
+                    writer.flush()?;
+                    return Ok(replaced);
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the pieces of a split stream, created by `PyRegex::split_stream`.
+pub struct SplitStream<R> {
+    re: PyRegex,
+    input: Decoder<R>,
+    /// The text after the current piece's searched part, after the context before it.
+    window: Window,
+    /// The searched part of the current piece, moved out of the window so that each search
+    /// only covers the new input and its context.
+    piece: String,
+    done: bool,
+}
+
+impl<R: BufRead> SplitStream<R> {
+    fn next_piece(&mut self) -> io::Result<Option<String>> {
+        loop {
+            let eof = self.input.eof();
+            match self
+                .window
+                .search(&self.re, eof)
+                .map_err(io::Error::other)?
+            {
+                Found::Match(range, _) => {
+                    self.piece
+                        .push_str(&self.window.text()[self.window.scan()..range.start]);
+                    self.window.advance(range.end);
+                    return Ok(Some(mem::take(&mut self.piece)));
+                }
+                Found::NeedMore(start) => {
+                    self.piece
+                        .push_str(&self.window.text()[self.window.scan()..start]);
+                    self.window.advance(start);
+                    self.input.read_into(self.window.text_mut())?;
+                }
+                Found::None => {
+                    self.done = true;
+                    self.piece.push_str(self.window.unprocessed());
+                    self.window.clear();
+                    return Ok(Some(mem::take(&mut self.piece)));
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SplitStream<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let piece = self.next_piece();
+        if piece.is_err() {
+            self.done = true;
+        }
+        piece.transpose()
+    }
+}
+
+/// A match found by an `IncrementalMatcher`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamMatch {
+    /// The byte range of the match in the whole input fed so far.
+    pub range: Range<usize>,
+    /// The matched text.
+    pub text: String,
+}
+
+/// Finds the matches of a pattern in input that arrives in chunks, such as data read from a
+/// socket, without buffering more than the text that a match could still start in.
+///
+/// Partial matching (`partial=True`) tells "no match" apart from "need more input", so a match
+/// is only emitted once more input can no longer extend it (a match ending at the end of the input
+/// so far waits for the next chunk or `finish`), and matches spanning chunk boundaries are found.
+/// The last 256 characters before the earliest position a match can start are kept, so `\b`,
+/// anchors and lookbehinds see the text before a match, and the matches are the same as those of
+/// `find_iter` on the whole input however it is split into chunks (unless a lookbehind reaches
+/// further back). Empty matches are skipped. Requires the `regex` backend.
+///
+/// ```
+/// use py_regex::{IncrementalMatcher, PyRegex};
+///
+/// let mut matcher = IncrementalMatcher::new(PyRegex::new(r"\d+;")?);
+/// assert!(matcher.feed(b"id=12")?.is_empty());
+/// let found = matcher.feed(b"3; id=4")?;
+/// assert_eq!((found[0].range.clone(), found[0].text.as_str()), (3..7, "123;"));
+/// assert!(matcher.finish()?.is_empty());
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncrementalMatcher {
+    re: PyRegex,
+    /// Decoded text that a match may still start in, after the context before it.
+    window: Window,
+    /// Bytes of a UTF-8 sequence split across chunks.
+    pending: Vec<u8>,
+    /// The byte offset of the window's text in the whole input.
+    offset: usize,
+}
+
+impl IncrementalMatcher {
+    /// Creates a matcher for the pattern with no input yet.
+    pub fn new(re: PyRegex) -> Self {
+        IncrementalMatcher {
+            re,
+            window: Window::default(),
+            pending: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Returns the byte offset in the whole input of the text that is still buffered.
+    pub fn offset(&self) -> usize {
+        self.offset + self.window.scan()
+    }
+
+    /// Returns the decoded text that is kept because a match may still start in it.
+    pub fn buffered(&self) -> &str {
+        self.window.unprocessed()
+    }
+
+    /// Appends a chunk of UTF-8 input, which may end in the middle of a character, and returns
+    /// the matches that it completes.
+    pub fn feed(&mut self, chunk: &[u8]) -> PyResult<Vec<StreamMatch>> {
+        decode_into(&mut self.pending, chunk, self.window.text_mut())
+            .map_err(|err| PyValueError::new_err(format!("input is not valid UTF-8: {err}")))?;
+        self.drain(false)
+    }
+
+    /// Marks the end of the input and returns the remaining matches, such as one that more input
+    /// could have extended. The matcher can be reused for a new input afterwards.
+    pub fn finish(&mut self) -> PyResult<Vec<StreamMatch>> {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            return Err(PyValueError::new_err(
+                "input ended in the middle of a UTF-8 sequence",
+            ));
+        }
+        let matches = self.drain(true);
+        self.window.clear();
+        self.offset = 0;
+        matches
+    }
+
+    /// Emits the final matches in the buffer and drops the text that no match can start in.
+    fn drain(&mut self, eof: bool) -> PyResult<Vec<StreamMatch>> {
+        let mut matches = Vec::new();
+        loop {
+            match self.window.search(&self.re, eof)? {
+                Found::Match(range, _) => {
+                    matches.push(StreamMatch {
+                        range: self.offset + range.start..self.offset + range.end,
+                        text: self.window.text()[range.clone()].to_string(),
+                    });
+                    self.offset += self.window.advance(range.end);
+                }
+                Found::NeedMore(start) => {
+                    self.offset += self.window.advance(start);
+                    return Ok(matches);
+                }
+                Found::None => return Ok(matches),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_split_stream() -> io::Result<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"<sep(?:arator)?>").map_err(io::Error::other)?;
+        let text = "é1<separator>two<sep>three<sep>";
+        // One-byte reads split the delimiters and the multi-byte character across chunks.
+        let pieces = re
+            .split_stream(BufReader::with_capacity(1, text.as_bytes()))
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(pieces, ["é1", "two", "three", ""]);
+        assert_eq!(
+            re.split_stream(&b"\xff"[..])
+                .next()
+                .unwrap()
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_stream_chunk_size() -> io::Result<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let text = "bobcat cat é foobar\ncatfish cat";
+        for pattern in [r"\bcat\b", r"(?<=foo)bar", r"(?m)^cat", r"\Abob"] {
+            let re = PyRegex::new(pattern).map_err(io::Error::other)?;
+            let expected = re.split(text).map_err(io::Error::other)?;
+            for capacity in 1..=text.len() {
+                let pieces = re
+                    .split_stream(BufReader::with_capacity(capacity, text.as_bytes()))
+                    .collect::<io::Result<Vec<_>>>()?;
+                assert_eq!(pieces, expected, "{pattern} with capacity {capacity}");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_stream_long_pieces() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let piece = "é".repeat(1000);
+        let text = [piece.as_str(); 3].join("--");
+        let re = PyRegex::new(r"(?<=é)--")?;
+        let mut split = re.split_stream(BufReader::with_capacity(7, text.as_bytes()));
+        for _ in 0..3 {
+            assert_eq!(split.next().transpose()?.as_deref(), Some(piece.as_str()));
+            // Only the context and the unsearched input are kept in the window.
+            assert!(split.window.text().chars().count() <= CONTEXT + 7);
+        }
+        assert!(split.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_stream() -> io::Result<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(\w+)@(é+)").map_err(io::Error::other)?;
+        let text = "to a@éé, b@é.";
+        let mut out = Vec::new();
+        let replaced = re.replace_stream(
+            BufReader::with_capacity(1, text.as_bytes()),
+            &mut out,
+            r"\2:\1",
+        )?;
+        assert_eq!(replaced, 2);
+        assert_eq!(String::from_utf8(out).unwrap(), "to éé:a, é:b.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_stream_chunk_size() -> io::Result<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let text = "bobcat cat é foobar\ncatfish cat";
+        for pattern in [r"\bcat\b", r"(?<=foo)bar", r"(?m)^cat", r"\Abob|\bé"] {
+            let re = PyRegex::new(pattern).map_err(io::Error::other)?;
+            let expected = re.replace(text, "X").map_err(io::Error::other)?;
+            for capacity in 1..=text.len() {
+                let mut out = Vec::new();
+                let input = BufReader::with_capacity(capacity, text.as_bytes());
+                re.replace_stream(input, &mut out, "X")?;
+                let out = String::from_utf8(out).unwrap();
+                assert_eq!(out, expected, "{pattern} with capacity {capacity}");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incremental_matcher() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let mut matcher = IncrementalMatcher::new(PyRegex::new(r"<(\w+)>|é+")?);
+        let mut found = Vec::new();
+        for chunk in "x<ab".as_bytes().chunks(1) {
+            found.extend(matcher.feed(chunk)?);
+        }
+        assert!(found.is_empty());
+        assert_eq!(matcher.buffered(), "<ab");
+        assert_eq!(matcher.offset(), 1);
+        // The second `é` is split across chunks and may be followed by more.
+        found.extend(matcher.feed(b"c> \xc3\xa9\xc3")?);
+        found.extend(matcher.feed(b"\xa9")?);
+        assert_eq!(found.len(), 1);
+        found.extend(matcher.finish()?);
+        let found = found.iter().map(|m| (m.range.clone(), m.text.as_str()));
+        assert_eq!(found.collect::<Vec<_>>(), [(1..6, "<abc>"), (7..11, "éé")]);
+
+        assert!(matcher.feed(b"\xff").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incremental_matcher_context() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let text = "bobcat cat é(?<=foo)foobar catfish\ncat.";
+        for pattern in [r"\bcat\b", r"(?<=foo)bar", r"(?m)^cat", r"\Acat|\bfo+"] {
+            let re = PyRegex::new(pattern)?;
+            let offsets = ByteOffsets::new(text);
+            let expected = re
+                .find_iter(text)?
+                .iter()
+                .map(|m| {
+                    let span = m.span(0)?.unwrap();
+                    Ok(offsets.get(span.start)..offsets.get(span.end))
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            let mut matcher = IncrementalMatcher::new(re);
+            let mut found = Vec::new();
+            for chunk in text.as_bytes().chunks(1) {
+                found.extend(matcher.feed(chunk)?);
+            }
+            found.extend(matcher.finish()?);
+            let found = found.into_iter().map(|m| m.range).collect::<Vec<_>>();
+            assert_eq!(found, expected, "{pattern}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_empty_matches() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        // `x*` matches empty at every position, and `b` must still be found where it did.
+        let re = PyRegex::new(r"x*|b")?;
+        let text = "abxxbéb";
+        let offsets = ByteOffsets::new(text);
+        let mut expected = Vec::new();
+        for m in re.find_iter(text)? {
+            let span = m.span(0)?.unwrap();
+            if !span.is_empty() {
+                expected.push(offsets.get(span.start)..offsets.get(span.end));
+            }
+        }
+        assert_eq!(expected, [1..2, 2..4, 4..5, 7..8]);
+
+        for capacity in 1..=text.len() {
+            let mut matcher = IncrementalMatcher::new(re.clone());
+            let mut found = Vec::new();
+            for chunk in text.as_bytes().chunks(capacity) {
+                found.extend(matcher.feed(chunk)?);
+            }
+            found.extend(matcher.finish()?);
+            let found = found.into_iter().map(|m| m.range).collect::<Vec<_>>();
+            assert_eq!(found, expected, "capacity {capacity}");
+
+            let input = BufReader::with_capacity(capacity, text.as_bytes());
+            let pieces = re.split_stream(input).collect::<io::Result<Vec<_>>>()?;
+            assert_eq!(pieces, ["a", "", "", "é", ""], "capacity {capacity}");
+
+            let mut out = Vec::new();
+            let input = BufReader::with_capacity(capacity, text.as_bytes());
+            assert_eq!(re.replace_stream(input, &mut out, "#")?, 4);
+            assert_eq!(
+                String::from_utf8(out).unwrap(),
+                "a###é#",
+                "capacity {capacity}"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use crate::python::PyRegex;
+use pyo3::PyErr;
+use pyo3::exceptions::PyValueError;
+use std::fmt;
+
+/// A problem with a replacement template, found by `PyRegex::check_template`. Offsets are byte
+/// offsets of the backslash that starts the offending escape.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TemplateError {
+    /// A numbered reference, such as `\3` or `\g<3>`, to a group the pattern does not have.
+    NoSuchGroup { group: usize, offset: usize },
+    /// A named reference, such as `\g<name>`, to a group the pattern does not have.
+    UnknownGroupName { name: String, offset: usize },
+    /// A malformed escape, such as `\q`, `\g<name` or a trailing backslash.
+    BadEscape { offset: usize },
+    /// Reading the groups of the pattern failed.
+    Python(PyErr),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::NoSuchGroup { group, offset } => {
+                write!(f, "no such group {group} at byte {offset}")
+            }
+            TemplateError::UnknownGroupName { name, offset } => {
+                write!(f, "unknown group name `{name}` at byte {offset}")
+            }
+            TemplateError::BadEscape { offset } => write!(f, "bad escape at byte {offset}"),
+            TemplateError::Python(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TemplateError::Python(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<PyErr> for TemplateError {
+    fn from(err: PyErr) -> Self {
+        TemplateError::Python(err)
+    }
+}
+
+/// Converts into a Python `ValueError`, or the original error for `TemplateError::Python`.
+impl From<TemplateError> for PyErr {
+    fn from(err: TemplateError) -> Self {
+        match err {
+            TemplateError::Python(err) => err,
+            err => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+impl PyRegex {
+    /// Checks that a replacement template for `replace` is well formed and that every group it
+    /// refers to with `\1` or `\g<name>` exists in the pattern, so that rewrite rules can be
+    /// rejected when they are loaded rather than on the first input that matches.
+    ///
+    /// The `regex` module only parses a template once there is something to replace. The check
+    /// follows its syntax, which also accepts `\x..`, `\u....`, `\U........` and `\N{...}`
+    /// escapes that the `re` fallback rejects. Unicode names in `\N{...}` are not checked.
+    ///
+    /// ```
+    /// use py_regex::{PyRegex, TemplateError};
+    ///
+    /// let re = PyRegex::new(r"(?P<user>\w+)@(\w+)")?;
+    /// assert!(re.check_template(r"\g<user> at \2").is_ok());
+    /// assert!(matches!(
+    ///     re.check_template(r"\g<host>"),
+    ///     Err(TemplateError::UnknownGroupName { .. })
+    /// ));
+    /// # Ok::<(), pyo3::PyErr>(())
+    /// ```
+    pub fn check_template(&self, replacement: &str) -> Result<(), TemplateError> {
+        let groups = self.groups_len()?;
+        let names = self.group_index()?;
+        let bytes = replacement.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] != b'\\' {
+                i += 1;
+                continue;
+            }
+            let offset = i;
+            let bad_escape = TemplateError::BadEscape { offset };
+            let escaped = &bytes[i + 1..];
+            let digits = |from: usize, radix: u32| {
+                escaped[from..]
+                    .iter()
+                    .take_while(|&&b| (b as char).is_digit(radix))
+                    .count()
+            };
+            i += 2 + match escaped.first().copied() {
+                None => return Err(bad_escape),
+                Some(b'g') => {
+                    let Some(len) = escaped.iter().position(|&b| b == b'>') else {
+                        return Err(bad_escape);
+                    };
+                    let name = replacement[offset + 2..offset + 1 + len]
+                        .strip_prefix('<')
+                        .ok_or(TemplateError::BadEscape { offset })?;
+                    if let Ok(group) = name.parse::<usize>() {
+                        if !name.bytes().all(|b| b.is_ascii_digit()) {
+                            return Err(bad_escape);
+                        }
+                        if group > groups {
+                            return Err(TemplateError::NoSuchGroup { group, offset });
+                        }
+                    } else if !is_identifier(name) {
+                        return Err(bad_escape);
+                    } else if !names.contains_key(name) {
+                        return Err(TemplateError::UnknownGroupName {
+                            name: name.to_string(),
+                            offset,
+                        });
+                    }
+                    len
+                }
+                // Octal escapes: `\0` followed by up to two octal digits, or three octal digits.
+                Some(b'0') => digits(1, 8).min(2),
+                Some(b'1'..=b'9') if digits(0, 8) >= 3 => 2,
+                Some(b'1'..=b'9') => {
+                    let len = digits(0, 10).min(2);
+                    let group = replacement[offset + 1..offset + 1 + len]
+                        .parse()
+                        .expect("ASCII digits");
+                    if group > groups {
+                        return Err(TemplateError::NoSuchGroup { group, offset });
+                    }
+                    len - 1
+                }
+                Some(b'x' | b'u' | b'U') => {
+                    let len = match escaped[0] {
+                        b'x' => 2,
+                        b'u' => 4,
+                        _ => 8,
+                    };
+                    if digits(1, 16) < len {
+                        return Err(bad_escape);
+                    }
+                    len
+                }
+                Some(b'N') => match escaped.iter().position(|&b| b == b'}') {
+                    Some(end) if escaped.get(1) == Some(&b'{') && end > 2 => end,
+                    _ => return Err(bad_escape),
+                },
+                Some(b'a' | b'b' | b'f' | b'n' | b'r' | b't' | b'v') => 0,
+                Some(b) if b.is_ascii_alphabetic() => return Err(bad_escape),
+                // Any other character, including a backslash, stands for itself.
+                Some(_) => 0,
+            };
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c == '_' || c.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::PyResult;
+
+    #[test]
+    fn test_check_template() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let re = PyRegex::new(r"(?P<a>x)(y)")?;
+        for template in [
+            r"\g<a>\2\g<0>",
+            r"\\\-é\n\0\012\777",
+            r"\x41é\N{BULLET}",
+            "",
+        ] {
+            assert!(re.check_template(template).is_ok(), "{template}");
+        }
+        let check = |template| re.check_template(template).unwrap_err().to_string();
+        assert_eq!(check(r"ab\3"), "no such group 3 at byte 2");
+        assert_eq!(check(r"\12"), "no such group 12 at byte 0");
+        assert_eq!(check(r"\g<3>"), "no such group 3 at byte 0");
+        assert_eq!(check(r"\g<b>"), "unknown group name `b` at byte 0");
+        for template in [
+            r"\q", r"x\", r"\g<a", r"\g<>", r"\g<1a>", r"\g<+1>", r"\g", r"\x4",
+        ] {
+            assert_eq!(
+                check(template),
+                format!("bad escape at byte {}", template.find('\\').unwrap())
+            );
+        }
+
+        Ok(())
+    }
+}
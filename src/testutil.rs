@@ -0,0 +1,142 @@
+//! Assertions for testing patterns, with failure messages showing the pattern, the text and how
+//! the groups differ.
+//!
+//! ```
+//! use py_regex::{assert_captures, assert_matches};
+//!
+//! assert_matches!(r"\d+", "order 66");
+//! assert_captures!(r"(?P<key>\w+)=(?P<value>\w+)", "a=1", { "key" => "a", "value" => "1" });
+//! ```
+
+use crate::python::PyRegex;
+use std::fmt::Write;
+
+/// Patterns accepted by the assertion macros: `&str` or `&PyRegex`.
+pub trait TestPattern {
+    /// Compiles the pattern, panicking if it is invalid.
+    fn to_regex(&self) -> PyRegex;
+}
+
+impl TestPattern for str {
+    #[track_caller]
+    fn to_regex(&self) -> PyRegex {
+        PyRegex::new(self).unwrap_or_else(|err| panic!("invalid pattern `{self}`: {err}"))
+    }
+}
+
+impl TestPattern for PyRegex {
+    fn to_regex(&self) -> PyRegex {
+        self.clone()
+    }
+}
+
+/// Asserts that the pattern matches somewhere in the text.
+///
+/// ```should_panic
+/// py_regex::assert_matches!(r"^\d+$", "12a");
+/// ```
+#[macro_export]
+macro_rules! assert_matches {
+    ($pattern:expr, $text:expr $(,)?) => {
+        $crate::testutil::check_matches(&$crate::testutil::TestPattern::to_regex($pattern), $text)
+    };
+}
+
+/// Asserts that the pattern matches the text and that the named groups of the first match have
+/// the expected values.
+#[macro_export]
+macro_rules! assert_captures {
+    ($pattern:expr, $text:expr, { $($name:expr => $value:expr),* $(,)? } $(,)?) => {
+        $crate::testutil::check_captures(
+            &$crate::testutil::TestPattern::to_regex($pattern),
+            $text,
+            &[$(($name, $value)),*],
+        )
+    };
+}
+
+/// Implementation of `assert_matches!`.
+#[track_caller]
+pub fn check_matches(re: &PyRegex, text: &str) {
+    match re.is_match(text) {
+        Ok(true) => {}
+        Ok(false) => panic!("{}", header("pattern does not match", re, text)),
+        Err(err) => panic!("{}\n   error: {err}", header("matching failed", re, text)),
+    }
+}
+
+/// Implementation of `assert_captures!`.
+#[track_caller]
+pub fn check_captures(re: &PyRegex, text: &str, expected: &[(&str, &str)]) {
+    let data = match re
+        .search_match(text)
+        .and_then(|m| m.map(|m| m.to_data()).transpose())
+    {
+        Ok(Some(data)) => data,
+        Ok(None) => panic!("{}", header("pattern does not match", re, text)),
+        Err(err) => panic!("{}\n   error: {err}", header("matching failed", re, text)),
+    };
+    let mut diff = String::new();
+    for &(name, value) in expected {
+        match data.name(name) {
+            Some(actual) if actual == value => {}
+            Some(actual) => {
+                let _ = write!(
+                    diff,
+                    "\n  group `{name}`: expected {value:?}, got {actual:?}"
+                );
+            }
+            None if data.names().contains_key(name) => {
+                let _ = write!(
+                    diff,
+                    "\n  group `{name}`: expected {value:?}, but it did not participate"
+                );
+            }
+            None => {
+                let _ = write!(diff, "\n  group `{name}`: no such group");
+            }
+        }
+    }
+    if !diff.is_empty() {
+        panic!(
+            "{}\n   match: {:?}{diff}",
+            header("captures differ", re, text),
+            data.as_str()
+        );
+    }
+}
+
+fn header(problem: &str, re: &PyRegex, text: &str) -> String {
+    let pattern = re.pattern().unwrap_or_default();
+    format!("assertion failed: {problem}\n pattern: `{pattern}`\n    text: {text:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::PyResult;
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn test_assertions() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        assert_matches!(r"\d+", "a1");
+        let re = crate::PyRegex::new(r"(?P<key>\w+)=(?P<value>\d+)?")?;
+        assert_captures!(&re, "x=1", { "key" => "x", "value" => "1" });
+
+        let message = panic::catch_unwind(AssertUnwindSafe(|| {
+            assert_captures!(&re, "x=", { "key" => "y", "value" => "1", "other" => "" });
+        }))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+        assert_eq!(
+            *message,
+            "assertion failed: captures differ\n pattern: `(?P<key>\\w+)=(?P<value>\\d+)?`\n    \
+             text: \"x=\"\n   match: \"x=\"\n  group `key`: expected \"y\", got \"x\"\n  \
+             group `value`: expected \"1\", but it did not participate\n  group `other`: no such group"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+//! Instrumentation of the Python calls: `tracing` spans with the `tracing` feature and per-pattern
+//! counters with the `metrics` feature.
+
+#[cfg(feature = "metrics")]
+use crate::metrics::{Counters, Outcome};
+use crate::python::with_traceback;
+use pyo3::PyResult;
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
+
+/// Longest pattern prefix recorded in spans, in characters.
+#[cfg(feature = "tracing")]
+const MAX_PATTERN_LEN: usize = 64;
+
+/// An operation in progress, recorded as an entered `py_regex` debug span.
+pub(crate) struct Operation {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    op: &'static str,
+    #[cfg(feature = "metrics")]
+    counters: Option<Arc<Counters>>,
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    start: std::time::Instant,
+}
+
+/// Starts an operation, recording its name, the pattern (truncated) and the haystack length.
+/// The pattern and the length are only read if the span is enabled.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+#[inline(always)]
+pub(crate) fn start(
+    op: &'static str,
+    pattern: impl FnOnce() -> String,
+    haystack_len: impl FnOnce() -> Option<usize>,
+) -> Operation {
+    #[cfg(feature = "tracing")]
+    let span = {
+        let span = tracing::debug_span!(
+            "py_regex",
+            op,
+            pattern = tracing::field::Empty,
+            haystack_len = tracing::field::Empty
+        );
+        if !span.is_disabled() {
+            if let Some(len) = haystack_len() {
+                span.record("haystack_len", len);
+            }
+            let pattern = pattern();
+            let truncated = match pattern.char_indices().nth(MAX_PATTERN_LEN) {
+                Some((end, _)) => format!("{}…", &pattern[..end]),
+                None => pattern,
+            };
+            span.record("pattern", truncated.as_str());
+        }
+        span.entered()
+    };
+    Operation {
+        #[cfg(feature = "tracing")]
+        span,
+        #[cfg(feature = "tracing")]
+        op,
+        #[cfg(feature = "metrics")]
+        counters: None,
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        start: std::time::Instant::now(),
+    }
+}
+
+impl Operation {
+    /// Also records the operation in the counters of a pattern handle.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn counted(mut self, counters: &Arc<Counters>) -> Self {
+        self.counters = Some(Arc::clone(counters));
+        self
+    }
+
+    /// Ends the operation, emitting an event with the elapsed time, or a warning if it failed.
+    /// Errors get the Python traceback appended to their message.
+    #[inline(always)]
+    pub(crate) fn finish<T>(self, result: PyResult<T>) -> PyResult<T> {
+        self.finish_counting(result, |_| 0)
+    }
+
+    /// Same as `finish`, counting the matches found with `matches`.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    #[inline(always)]
+    pub(crate) fn finish_counting<T>(
+        self,
+        result: PyResult<T>,
+        matches: impl FnOnce(&T) -> usize,
+    ) -> PyResult<T> {
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        let elapsed = self.start.elapsed();
+        #[cfg(feature = "tracing")]
+        {
+            let op = self.op;
+            match &result {
+                Ok(_) => tracing::debug!(?elapsed, "{op} finished"),
+                Err(err) => tracing::warn!(?elapsed, error = %err, "{op} failed"),
+            }
+            drop(self.span);
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(counters) = &self.counters {
+            let (found, outcome) = match &result {
+                Ok(value) => (matches(value), Outcome::Ok),
+                Err(err) => {
+                    let timeout = pyo3::Python::with_gil(|py| {
+                        err.is_instance_of::<pyo3::exceptions::PyTimeoutError>(py)
+                    });
+                    (
+                        0,
+                        if timeout {
+                            Outcome::Timeout
+                        } else {
+                            Outcome::Error
+                        },
+                    )
+                }
+            };
+            counters.record(elapsed, found, outcome);
+        }
+        result.map_err(with_traceback)
+    }
+}
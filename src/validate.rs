@@ -0,0 +1,176 @@
+use crate::python::PyRegex;
+use pyo3::PyResult;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Why a field failed validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldErrorKind {
+    /// The field is absent (or `null` in a JSON value).
+    Missing,
+    /// The value does not match the field's pattern.
+    Mismatch,
+    /// The JSON value is an array or an object.
+    WrongType,
+}
+
+/// A field that failed validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// The name of the field.
+    pub field: String,
+    /// Why it failed.
+    pub kind: FieldErrorKind,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.kind {
+            FieldErrorKind::Missing => "is missing",
+            FieldErrorKind::Mismatch => "does not match the expected format",
+            FieldErrorKind::WrongType => "must be a string, a number or a boolean",
+        };
+        write!(f, "{} {reason}", self.field)
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// Validates records field by field, each field against a pattern that must match the whole
+/// value.
+///
+/// ```
+/// use py_regex::Validator;
+/// use std::collections::HashMap;
+///
+/// let validator = Validator::new()
+///     .field("zip", r"\d{5}")?
+///     .fuzzy_field("country", "Germany|France", 1)?;
+/// let form = HashMap::from([
+///     ("zip".to_string(), "1234".to_string()),
+///     ("country".to_string(), "Frence".to_string()),
+/// ]);
+/// let errors = validator.validate(&form)?;
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].field, "zip");
+/// # Ok::<(), pyo3::PyErr>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Validator {
+    fields: Vec<(String, PyRegex)>,
+}
+
+impl Validator {
+    /// Creates a validator without fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a required field whose whole value must match the pattern.
+    pub fn field(self, name: &str, pattern: &str) -> PyResult<Self> {
+        self.add(name, format!(r"\A(?:{pattern})\Z"))
+    }
+
+    /// Adds a required field whose whole value must match the pattern with at most `max_errors`
+    /// insertions, deletions or substitutions. Requires the `regex` backend.
+    pub fn fuzzy_field(self, name: &str, pattern: &str, max_errors: u32) -> PyResult<Self> {
+        self.add(name, format!(r"\A(?:{pattern}){{e<={max_errors}}}\Z"))
+    }
+
+    fn add(mut self, name: &str, pattern: String) -> PyResult<Self> {
+        self.fields
+            .push((name.to_string(), PyRegex::new(&pattern)?));
+        Ok(self)
+    }
+
+    /// Returns the errors of the record, in the order the fields were added, or an empty vector
+    /// if it is valid. Fields without a rule are ignored.
+    pub fn validate(&self, record: &HashMap<String, String>) -> PyResult<Vec<FieldError>> {
+        self.check(|name| Ok(record.get(name).map(|text| Cow::Borrowed(text.as_str()))))
+    }
+
+    /// Same as `validate`, for the fields of a JSON object. Numbers and booleans are validated
+    /// by their JSON text. A value that is not an object has every field missing.
+    #[cfg(feature = "json")]
+    pub fn validate_json(&self, record: &serde_json::Value) -> PyResult<Vec<FieldError>> {
+        use serde_json::Value;
+
+        self.check(|name| match record.get(name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(text)) => Ok(Some(Cow::Borrowed(text))),
+            Some(value @ (Value::Number(_) | Value::Bool(_))) => {
+                Ok(Some(Cow::Owned(value.to_string())))
+            }
+            Some(Value::Array(_) | Value::Object(_)) => Err(FieldErrorKind::WrongType),
+        })
+    }
+
+    fn check<'r>(
+        &self,
+        value: impl Fn(&str) -> Result<Option<Cow<'r, str>>, FieldErrorKind>,
+    ) -> PyResult<Vec<FieldError>> {
+        let mut errors = Vec::new();
+        for (name, re) in &self.fields {
+            let kind = match value(name) {
+                Ok(Some(text)) if re.is_match(&*text)? => continue,
+                Ok(Some(_)) => FieldErrorKind::Mismatch,
+                Ok(None) => FieldErrorKind::Missing,
+                Err(kind) => kind,
+            };
+            errors.push(FieldError {
+                field: name.clone(),
+                kind,
+            });
+        }
+        Ok(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validator() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let validator = Validator::new()
+            .field("zip", r"\d{5}")?
+            .fuzzy_field("country", "Germany|France", 1)?
+            .field("email", r"[^@\s]+@[^@\s]+")?;
+        let record = HashMap::from([
+            ("zip".to_string(), "123456".to_string()),
+            ("country".to_string(), "Frnce".to_string()),
+        ]);
+        let errors = validator.validate(&record)?;
+        assert_eq!(
+            errors,
+            [
+                FieldError {
+                    field: "zip".to_string(),
+                    kind: FieldErrorKind::Mismatch,
+                },
+                FieldError {
+                    field: "email".to_string(),
+                    kind: FieldErrorKind::Missing,
+                },
+            ]
+        );
+        assert_eq!(errors[1].to_string(), "email is missing");
+
+        #[cfg(feature = "json")]
+        assert_eq!(
+            validator
+                .validate_json(
+                    &serde_json::json!({"zip": 12345, "country": "France", "email": []})
+                )?
+                .iter()
+                .map(|error| &error.kind)
+                .collect::<Vec<_>>(),
+            [&FieldErrorKind::WrongType]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+//! A dedicated thread for all Python interaction.
+//!
+//! Every call into the crate otherwise takes the GIL on the calling thread, which can deadlock
+//! with other pyo3 users in the process that hold the GIL while waiting on a lock the caller holds.
+//! A `Worker` owns one OS thread that compiles and runs the patterns; application threads only
+//! exchange messages with it and receive owned results:
+//!
+//! ```
+//! use py_regex::worker::Worker;
+//!
+//! let worker = Worker::new()?;
+//! let re = worker.compile(r"(?P<user>\w+)@(?P<host>\w+)")?;
+//! let m = re.search("mail user@host")?.unwrap();
+//! assert_eq!(&m["host"], "host");
+//! # Ok::<(), pyo3::PyErr>(())
+//! ```
+//!
+//! `WorkerRegex::submit` queues a search without waiting for it, so many jobs can be outstanding at
+//! once. The queue is bounded: submitting blocks while it is full. `WorkerRegex::with_cancellation`
+//! makes jobs that have not started yet fail instead.
+
+use crate::cancel::CancellationToken;
+use crate::flags::Flags;
+use crate::match_data::MatchData;
+use crate::python::PyRegex;
+use pyo3::PyResult;
+use pyo3::exceptions::PyRuntimeError;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread;
+
+/// Patterns compiled on the worker thread, by id.
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    patterns: HashMap<u64, PyRegex>,
+}
+
+type Job = Box<dyn FnOnce(&mut Registry) + Send>;
+
+/// Number of queued jobs after which `Worker::new` makes senders wait.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Handle to a thread that performs all Python interaction for the patterns it compiles.
+///
+/// The thread exits once the `Worker` and every `WorkerRegex` compiled by it are dropped.
+#[derive(Clone)]
+pub struct Worker {
+    sender: SyncSender<Job>,
+}
+
+impl Worker {
+    /// Spawns the worker thread with a queue of 1024 jobs.
+    pub fn new() -> PyResult<Self> {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Spawns the worker thread with a queue of `capacity` jobs.
+    pub fn with_capacity(capacity: usize) -> PyResult<Self> {
+        let (sender, receiver) = mpsc::sync_channel::<Job>(capacity);
+        thread::Builder::new()
+            .name("py-regex-worker".to_string())
+            .spawn(move || {
+                let mut registry = Registry::default();
+                for job in receiver {
+                    job(&mut registry);
+                }
+            })?;
+        Ok(Worker { sender })
+    }
+
+    /// Compiles the pattern on the worker thread.
+    pub fn compile(&self, pattern: &str) -> PyResult<WorkerRegex> {
+        self.compile_with_flags(pattern, Flags::NONE)
+    }
+
+    /// Compiles the pattern with the given flags on the worker thread.
+    pub fn compile_with_flags(&self, pattern: &str, flags: Flags) -> PyResult<WorkerRegex> {
+        let pattern = pattern.to_string();
+        let id = call(&self.sender, move |registry| {
+            let re = PyRegex::with_flags(&pattern, flags)?;
+            registry.next_id += 1;
+            registry.patterns.insert(registry.next_id, re);
+            Ok(registry.next_id)
+        })?;
+        Ok(WorkerRegex {
+            id,
+            sender: self.sender.clone(),
+            cancellation: None,
+        })
+    }
+
+    /// Runs `f` on the worker thread and returns its result, for operations without a dedicated
+    /// method.
+    pub fn run<T, F>(&self, f: F) -> PyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> PyResult<T> + Send + 'static,
+    {
+        call(&self.sender, move |_| f())
+    }
+}
+
+impl fmt::Debug for Worker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Worker").finish_non_exhaustive()
+    }
+}
+
+/// A pattern compiled by a `Worker`. Its methods run on the worker thread and return owned data.
+pub struct WorkerRegex {
+    id: u64,
+    sender: SyncSender<Job>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl WorkerRegex {
+    /// Makes the methods of this pattern check `token` when called and again when their job
+    /// starts on the worker thread, and fail with a `Cancelled` error once it is cancelled. Jobs
+    /// queued by `submit` before the cancellation are skipped the same way. See
+    /// `CancellationToken`.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Returns a `Cancelled` error if the cancellation token was cancelled.
+    fn check_cancelled(token: &Option<CancellationToken>) -> PyResult<()> {
+        token.as_ref().map_or(Ok(()), CancellationToken::check)
+    }
+
+    /// Queues `f` with the compiled pattern on the worker thread.
+    fn send<T, F>(&self, text: &str, f: F) -> PyResult<Receiver<PyResult<T>>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&PyRegex, &str) -> PyResult<T> + Send + 'static,
+    {
+        Self::check_cancelled(&self.cancellation)?;
+        let (id, text, token) = (self.id, text.to_string(), self.cancellation.clone());
+        send(&self.sender, move |registry| {
+            Self::check_cancelled(&token)?;
+            f(&registry.patterns[&id], &text)
+        })
+    }
+
+    /// Runs `f` with the compiled pattern on the worker thread and waits for its result.
+    fn call<T, F>(&self, text: &str, f: F) -> PyResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&PyRegex, &str) -> PyResult<T> + Send + 'static,
+    {
+        wait(self.send(text, f)?)
+    }
+
+    /// Queues a search for all matches in the text and returns the receiver of its result,
+    /// which arrives once. Blocks while the worker's queue is full.
+    pub fn submit(&self, text: &str) -> PyResult<Receiver<PyResult<Vec<MatchData>>>> {
+        self.send(text, |re, text| {
+            re.find_iter(text)?.iter().map(|m| m.to_data()).collect()
+        })
+    }
+
+    /// Returns `true` if the pattern matches anywhere in the text.
+    pub fn is_match(&self, text: &str) -> PyResult<bool> {
+        self.call(text, |re, text| re.is_match(text))
+    }
+
+    /// Returns the first match.
+    pub fn search(&self, text: &str) -> PyResult<Option<MatchData>> {
+        self.call(text, |re, text| {
+            re.search_match(text)?.map(|m| m.to_data()).transpose()
+        })
+    }
+
+    /// Returns all non-overlapping matches.
+    pub fn find_iter(&self, text: &str) -> PyResult<Vec<MatchData>> {
+        self.call(text, |re, text| {
+            re.find_iter(text)?.iter().map(|m| m.to_data()).collect()
+        })
+    }
+
+    /// Returns all matches like Python's `findall`.
+    pub fn find_all(&self, text: &str) -> PyResult<Vec<String>> {
+        self.call(text, |re, text| re.find_all(text))
+    }
+
+    /// Replaces all matches, as with `PyRegex::replace`.
+    pub fn replace(&self, text: &str, replacement: &str) -> PyResult<String> {
+        let replacement = replacement.to_string();
+        self.call(text, move |re, text| re.replace(text, &replacement))
+    }
+
+    /// Splits the text by the matches, as with `PyRegex::split`.
+    pub fn split(&self, text: &str) -> PyResult<Vec<String>> {
+        self.call(text, |re, text| re.split(text))
+    }
+}
+
+impl fmt::Debug for WorkerRegex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkerRegex")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for WorkerRegex {
+    /// Releases the compiled pattern on the worker thread.
+    fn drop(&mut self) {
+        let id = self.id;
+        let _ = self.sender.send(Box::new(move |registry: &mut Registry| {
+            registry.patterns.remove(&id);
+        }));
+    }
+}
+
+/// Sends `job` to the worker thread and returns the receiver of its result.
+fn send<T, F>(sender: &SyncSender<Job>, job: F) -> PyResult<Receiver<PyResult<T>>>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Registry) -> PyResult<T> + Send + 'static,
+{
+    let (reply, result) = mpsc::sync_channel(1);
+    sender
+        .send(Box::new(move |registry: &mut Registry| {
+            let _ = reply.send(job(registry));
+        }))
+        .map_err(|_| stopped())?;
+    Ok(result)
+}
+
+/// Sends `job` to the worker thread and waits for its result.
+fn call<T, F>(sender: &SyncSender<Job>, job: F) -> PyResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Registry) -> PyResult<T> + Send + 'static,
+{
+    wait(send(sender, job)?)
+}
+
+/// Waits for the result of a job.
+fn wait<T>(result: Receiver<PyResult<T>>) -> PyResult<T> {
+    result.recv().map_err(|_| stopped())?
+}
+
+/// The error returned when the worker thread is gone, e.g. after a panic in a job.
+fn stopped() -> pyo3::PyErr {
+    PyRuntimeError::new_err("the py-regex worker thread has stopped")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        let worker = Worker::new()?;
+        let re = worker.compile(r"(?P<user>\w+)@(?P<host>\w+)")?;
+        let m = re.search("mail user@host")?.unwrap();
+        assert_eq!(&m["host"], "host");
+        assert_eq!(re.find_iter("a@b c@d")?.len(), 2);
+        assert!(worker.compile("(").is_err());
+        assert!(worker.run(|| PyRegex::new("x")?.is_match("xyz"))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_worker_submit() -> PyResult<()> {
+        pyo3::prepare_freethreaded_python();
+
+        // More jobs than the queue holds, so `submit` waits for the worker to catch up.
+        let worker = Worker::with_capacity(1)?;
+        let re = worker.compile(r"(?P<user>\w+)@(?P<host>\w+)")?;
+        let pending = ["a@b", "none", "c@d e@f"]
+            .iter()
+            .map(|text| re.submit(text))
+            .collect::<PyResult<Vec<_>>>()?;
+        let counts = pending
+            .into_iter()
+            .map(|result| Ok(wait(result)?.len()))
+            .collect::<PyResult<Vec<_>>>()?;
+        assert_eq!(counts, [1, 0, 2]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+//! `regex.DEFAULT_VERSION` is global to the interpreter, so changing it is tested in its own test
+//! binary instead of next to tests that compile patterns concurrently.
+#![cfg(feature = "python")]
+
+use py_regex::{Flags, PyRegex, PyResult, Version, set_default_version};
+
+#[test]
+fn test_set_default_version() -> PyResult<()> {
+    pyo3::prepare_freethreaded_python();
+    // A set difference in VERSION1; a class, a literal `--`, a class and a `]` in VERSION0.
+    let pattern = "[[a-z]--[aeiou]]";
+    assert!(PyRegex::cached(pattern)?.find_all("abc")?.is_empty());
+
+    set_default_version(Version::V1)?;
+    assert_eq!(PyRegex::new(pattern)?.find_all("abc")?, ["b", "c"]);
+    // The cached VERSION0 pattern was dropped.
+    assert_eq!(PyRegex::cached(pattern)?.find_all("abc")?, ["b", "c"]);
+    // An explicit version flag still wins.
+    let v0 = PyRegex::with_flags(pattern, Flags::VERSION0)?;
+    assert_eq!(v0.find_all("a--e]")?, ["a--e]"]);
+
+    set_default_version(Version::V0)?;
+    assert!(PyRegex::new(pattern)?.find_all("abc")?.is_empty());
+    assert!(PyRegex::cached(pattern)?.find_all("abc")?.is_empty());
+
+    Ok(())
+}
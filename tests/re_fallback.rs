@@ -0,0 +1,50 @@
+//! The backend is resolved once per process, so the `re` fallback is tested in its own test
+//! binary, with `regex` hidden from the interpreter before the first pattern is compiled.
+#![cfg(feature = "python")]
+
+use py_regex::{Backend, Feature, Flags, PyRegex, UnsupportedFeature, backend, backend_info};
+use pyo3::prelude::*;
+use std::time::Duration;
+
+#[test]
+fn test_re_fallback() -> PyResult<()> {
+    pyo3::prepare_freethreaded_python();
+    // A `None` entry in `sys.modules` makes `import regex` raise an `ImportError`.
+    Python::with_gil(|py| {
+        py.import("sys")?
+            .getattr("modules")?
+            .set_item("regex", py.None())
+    })?;
+    py_regex::set_re_fallback(true);
+
+    assert_eq!(backend()?, Backend::Re);
+    let info = backend_info()?;
+    assert!(!info.supports(Feature::Fuzzy));
+    assert!(!info.supports(Feature::Version1));
+
+    let re = PyRegex::new(r"(?P<word>\w+)@(\d+)")?;
+    let m = re.search_match("at alice@42 now")?.unwrap();
+    assert_eq!(m.group("word")?.as_deref(), Some("alice"));
+    assert_eq!(m.group(2)?.as_deref(), Some("42"));
+    assert_eq!(
+        re.replace("alice@42, bob@7", r"\2:\g<word>")?,
+        "42:alice, 7:bob"
+    );
+    let ascii = PyRegex::with_flags(r"\w+", Flags::ASCII)?;
+    assert_eq!(ascii.find_all("héllo")?, ["h", "llo"]);
+
+    let is_unsupported =
+        |err: PyErr| Python::with_gil(|py| err.is_instance_of::<UnsupportedFeature>(py));
+    assert!(is_unsupported(PyRegex::new("(?:rust){e<=1}").unwrap_err()));
+    assert!(is_unsupported(
+        PyRegex::with_flags("a", Flags::VERSION1).unwrap_err()
+    ));
+    assert!(is_unsupported(
+        PyRegex::with_flags("a", Flags::POSIX).unwrap_err()
+    ));
+    assert!(is_unsupported(
+        re.with_timeout(Duration::from_secs(1)).unwrap_err()
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+//! `shutdown` drops caches shared by the whole process, so it is tested in its own test binary
+//! instead of next to tests that rely on `PyRegex::cached` returning the same pattern.
+#![cfg(feature = "python")]
+
+use py_regex::{PyRegex, PyResult, Runtime, shutdown};
+use std::sync::Arc;
+
+#[test]
+fn test_shutdown() -> PyResult<()> {
+    let a = {
+        let _runtime = Runtime::new();
+        let a = PyRegex::cached(r"\d+")?;
+        assert!(Arc::ptr_eq(&a, &PyRegex::cached(r"\d+")?));
+        a
+    };
+    let b = PyRegex::cached(r"\d+")?;
+    assert!(!Arc::ptr_eq(&a, &b));
+
+    shutdown()?;
+    let c = PyRegex::cached(r"\d+")?;
+    assert!(!Arc::ptr_eq(&b, &c));
+    // Handles held across a shutdown keep working.
+    assert!(a.is_match("7")? && b.is_match("7")? && c.is_match("7")?);
+    assert_eq!(a.find_iter("1 2")?[1].to_data()?.get(0), Some("2"));
+    assert_eq!(py_regex::sub(r"\d", "#", "a1")?, "a#");
+
+    Ok(())
+}